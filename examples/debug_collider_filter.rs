@@ -0,0 +1,35 @@
+use bevy::prelude::*;
+use bevy_rancic::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            RapierPhysicsPlugin::<NoUserData>::default(),
+            RapierDebugRenderPlugin::default(),
+            RancicPlugin,
+        ))
+        .add_systems(Startup, spawn_colliders)
+        .run();
+}
+
+fn spawn_colliders(
+    mut commands: Commands,
+    mut ev_toggle_debug_state: EventWriter<ToggleDebugStateEvent>,
+) {
+    for x in [-50.0, 0.0] {
+        commands.spawn((
+            Collider::ball(20.0),
+            TransformBundle::from_transform(Transform::from_xyz(x, 0.0, 0.0)),
+        ));
+    }
+    // Only the collider of this entity will be visible in debug mode.
+    commands.spawn((
+        Collider::ball(20.0),
+        TransformBundle::from_transform(Transform::from_xyz(50.0, 0.0, 0.0)),
+        DebugColliderFilter,
+    ));
+
+    ev_toggle_debug_state.send(ToggleDebugStateEvent);
+}
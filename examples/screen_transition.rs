@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use bevy_rancic::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Marker for everything that belongs to the current scene.
+#[derive(Component)]
+struct SceneEntity;
+
+/// Which of the two scenes is currently shown.
+#[derive(Resource, Default)]
+struct CurrentScene(bool);
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            RapierPhysicsPlugin::<NoUserData>::default(),
+            RancicPlugin,
+        ))
+        .init_resource::<CurrentScene>()
+        .add_systems(Startup, spawn_first_scene)
+        .add_systems(
+            Update,
+            (
+                start_transition,
+                swap_scene.run_if(on_event::<SceneSwapPoint>()),
+            ),
+        )
+        .run();
+}
+
+fn spawn_scene(commands: &mut Commands, current_scene: &CurrentScene) {
+    let color = if current_scene.0 {
+        Color::srgb(0.2, 0.4, 0.9)
+    } else {
+        Color::srgb(0.9, 0.4, 0.2)
+    };
+    commands.spawn((
+        SceneEntity,
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::splat(50.0)),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn spawn_first_scene(mut commands: Commands, current_scene: Res<CurrentScene>) {
+    spawn_scene(&mut commands, &current_scene);
+}
+
+/// Press space to go to the other scene.
+fn start_transition(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut ev_start_transition: EventWriter<StartScreenTransitionEvent>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        ev_start_transition.send(StartScreenTransitionEvent);
+    }
+}
+
+fn swap_scene(
+    mut commands: Commands,
+    mut current_scene: ResMut<CurrentScene>,
+    q_scene: Query<Entity, With<SceneEntity>>,
+) {
+    for entity in &q_scene {
+        commands.entity(entity).despawn_recursive();
+    }
+    current_scene.0 = !current_scene.0;
+    spawn_scene(&mut commands, &current_scene);
+}
@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+use bevy_rancic::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Path of the explosion sound, relative to the `assets` folder.
+/// Put any sound file there to run this example.
+const EXPLOSION_SOUND: &str = "explosion.ogg";
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            RapierPhysicsPlugin::<NoUserData>::default(),
+            RancicPlugin,
+        ))
+        .add_systems(Update, play_explosion)
+        .run();
+}
+
+/// Play an explosion at the cursor when clicking.
+/// It gets quieter the further away from the center of the screen you click.
+fn play_explosion(
+    assets: Res<AssetServer>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    q_window: Query<&Window>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut ev_play_spacial_sound: EventWriter<PlaySpacialSound>,
+) {
+    if !mouse_buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let (Ok(window), Ok((camera, camera_transform))) =
+        (q_window.get_single(), q_camera.get_single())
+    else {
+        return;
+    };
+    let Some(position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor))
+    else {
+        return;
+    };
+
+    ev_play_spacial_sound.send(PlaySpacialSound {
+        clip: assets.load(EXPLOSION_SOUND),
+        position,
+        ..default()
+    });
+}
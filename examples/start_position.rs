@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+use bevy_rancic::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Path of the music, relative to the `assets` folder.
+/// Put any sound file that is longer than a few seconds there to run this example.
+const MUSIC: &str = "music.ogg";
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            RapierPhysicsPlugin::<NoUserData>::default(),
+            RancicPlugin,
+        ))
+        .add_systems(Update, play_music)
+        .run();
+}
+
+/// Press space to play the music, starting 1.5 seconds in.
+fn play_music(
+    assets: Res<AssetServer>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut ev_play_sound: EventWriter<PlaySound>,
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    ev_play_sound.send(PlaySound {
+        clip: assets.load(MUSIC),
+        start_position: Some(1.5),
+        channel: SoundChannel::Music,
+        ..default()
+    });
+}
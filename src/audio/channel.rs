@@ -0,0 +1,204 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+
+use super::{sound::PlayingSounds, spacial::CulledSpacialVoices, AudioSystem, GameAudio};
+#[cfg(feature = "test-audio")]
+use super::{AudioRecorder, RecordedAudioCommand};
+
+/// The channel a sound is played on.
+/// Channels can be paused and resumed independently of each other.
+///
+/// Named `SoundChannel` so it doesn't clash with `bevy_kira_audio`'s `AudioChannel`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SoundChannel {
+    /// Background music.
+    Music,
+    /// Sound effects, the default channel.
+    #[default]
+    Sfx,
+    /// User interface sounds.
+    Ui,
+}
+
+impl SoundChannel {
+    /// All available channels.
+    pub const ALL: [SoundChannel; 3] = [SoundChannel::Music, SoundChannel::Sfx, SoundChannel::Ui];
+}
+
+/// A named group of sounds with its own volume, for example `AudioBus("weapons")`,
+/// see `GameAudio::set_bus_volume`.
+///
+/// Buses only scale the volume, on top of the channel, so the final volume of a sound is
+/// `volume * bus volume * channel volume * global volume`.
+/// Pausing still works per channel. If you only used channels to give groups of sounds
+/// their own volume slider, you can move those groups to buses and keep the channels as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AudioBus(pub &'static str);
+
+/// Send this `Event` to pause all sounds on every channel.
+#[derive(Event)]
+pub struct PauseAudioEvent;
+/// Send this `Event` to resume all sounds on every channel.
+/// This overrides any per-channel pause,
+/// so channels paused with `PauseChannelEvent` will be resumed too.
+#[derive(Event)]
+pub struct ResumeAudioEvent;
+/// Send this `Event` to pause all sounds on the given channel.
+/// Sounds played on a paused channel will start paused.
+#[derive(Event)]
+pub struct PauseChannelEvent(pub SoundChannel);
+/// Send this `Event` to resume all sounds on the given channel.
+#[derive(Event)]
+pub struct ResumeChannelEvent(pub SoundChannel);
+
+fn set_channel_paused(
+    channel: SoundChannel,
+    paused: bool,
+    game_audio: &mut GameAudio,
+    playing_sounds: &PlayingSounds,
+    culled_voices: &CulledSpacialVoices,
+    audio_instances: &mut Assets<AudioInstance>,
+    #[cfg(feature = "test-audio")] recorder: &mut AudioRecorder,
+) {
+    #[cfg(feature = "test-audio")]
+    recorder.record(RecordedAudioCommand::SetChannelPaused { channel, paused });
+
+    if paused {
+        game_audio.paused_channels.insert(channel);
+    } else {
+        game_audio.paused_channels.remove(&channel);
+    }
+
+    for sound in playing_sounds
+        .iter()
+        .filter(|sound| sound.channel == channel)
+    {
+        let Some(instance) = audio_instances.get_mut(&sound.handle) else {
+            continue;
+        };
+        if paused {
+            instance.pause(AudioTween::default());
+        } else if !culled_voices.contains(&sound.handle.id()) {
+            instance.resume(AudioTween::default());
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pause_channels(
+    mut game_audio: ResMut<GameAudio>,
+    playing_sounds: Res<PlayingSounds>,
+    culled_voices: Res<CulledSpacialVoices>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut ev_pause_audio: EventReader<PauseAudioEvent>,
+    mut ev_resume_audio: EventReader<ResumeAudioEvent>,
+    mut ev_pause_channel: EventReader<PauseChannelEvent>,
+    mut ev_resume_channel: EventReader<ResumeChannelEvent>,
+    #[cfg(feature = "test-audio")] mut recorder: ResMut<AudioRecorder>,
+) {
+    let mut changes = Vec::new();
+    for ev in ev_pause_channel.read() {
+        changes.push((ev.0, true));
+    }
+    for ev in ev_resume_channel.read() {
+        changes.push((ev.0, false));
+    }
+    // Global pause/resume is applied last so that it overrides per-channel state.
+    if ev_pause_audio.read().count() > 0 {
+        changes.extend(SoundChannel::ALL.map(|channel| (channel, true)));
+    }
+    if ev_resume_audio.read().count() > 0 {
+        changes.extend(SoundChannel::ALL.map(|channel| (channel, false)));
+    }
+
+    for (channel, paused) in changes {
+        set_channel_paused(
+            channel,
+            paused,
+            &mut game_audio,
+            &playing_sounds,
+            &culled_voices,
+            &mut audio_instances,
+            #[cfg(feature = "test-audio")]
+            &mut recorder,
+        );
+    }
+}
+
+pub struct SoundChannelPlugin;
+
+impl Plugin for SoundChannelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PauseAudioEvent>()
+            .add_event::<ResumeAudioEvent>()
+            .add_event::<PauseChannelEvent>()
+            .add_event::<ResumeChannelEvent>()
+            .add_systems(Update, pause_channels.in_set(AudioSystem::PlaySound));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::gizmos::gizmos::GizmoStorage;
+    use bevy_kira_audio::prelude::AudioSource;
+    use bevy_rapier2d::render::DebugRenderContext;
+
+    use super::*;
+    use crate::{
+        audio::{GameAudioPlugin, PlaySound},
+        utils::debug::DebugPlugin,
+    };
+
+    #[test]
+    fn pauses_sfx_while_music_keeps_playing() {
+        let mut gizmo_config = GizmoConfigStore::default();
+        gizmo_config.insert(GizmoConfig::default(), DefaultGizmoConfigGroup);
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .insert_resource(gizmo_config)
+            .init_resource::<DebugRenderContext>()
+            .init_resource::<GizmoStorage<DefaultGizmoConfigGroup, ()>>()
+            .add_plugins((DebugPlugin, GameAudioPlugin));
+
+        app.world_mut()
+            .send_event(PauseChannelEvent(SoundChannel::Sfx));
+        app.update();
+        let game_audio = app.world().resource::<GameAudio>();
+        assert!(game_audio.is_channel_paused(SoundChannel::Sfx));
+        assert!(!game_audio.is_channel_paused(SoundChannel::Music));
+
+        for (clip, channel) in [(1, SoundChannel::Sfx), (2, SoundChannel::Music)] {
+            app.world_mut().send_event(PlaySound {
+                clip: Handle::<AudioSource>::weak_from_u128(clip),
+                channel,
+                ..default()
+            });
+        }
+        app.update();
+        let playing_sounds = app.world().resource::<PlayingSounds>();
+        assert_eq!(playing_sounds.len(), 2);
+
+        // Sounds on the paused channel start paused, the music plays.
+        #[cfg(feature = "test-audio")]
+        {
+            let commands = app.world_mut().resource_mut::<AudioRecorder>().take();
+            let paused: Vec<(SoundChannel, bool)> = commands
+                .iter()
+                .filter_map(|command| match command {
+                    RecordedAudioCommand::Play {
+                        channel, paused, ..
+                    } => Some((*channel, *paused)),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(
+                paused,
+                [(SoundChannel::Sfx, true), (SoundChannel::Music, false)]
+            );
+            assert!(!commands.contains(&RecordedAudioCommand::SetChannelPaused {
+                channel: SoundChannel::Music,
+                paused: true,
+            }));
+        }
+    }
+}
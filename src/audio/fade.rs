@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+
+use super::{AudioSystem, GameAudio};
+
+/// Settings for fading the whole game audio in on startup and out on exit.
+/// Insert this resource before `Startup` to change them.
+///
+/// Fades apply to everything that uses the global volume,
+/// which means all sounds, including the ones that are already playing.
+#[derive(Resource)]
+pub struct AudioFadeSettings {
+    /// Fade the audio in from silence over this many seconds on startup.
+    /// Disabled (`None`) by default.
+    pub startup_fade_in: Option<f32>,
+    /// How many seconds the fade out of `FadeOutAndExitEvent` takes.
+    pub exit_fade_out: f32,
+    /// How many seconds the fade out of a `StopSound` takes,
+    /// unless it is `immediate`. A few milliseconds are enough to avoid clicks.
+    pub stop_fade_out: f32,
+}
+
+impl Default for AudioFadeSettings {
+    fn default() -> Self {
+        Self {
+            startup_fade_in: None,
+            exit_fade_out: 0.5,
+            stop_fade_out: 0.01,
+        }
+    }
+}
+
+/// Send this `Event` to fade out the audio and then exit the app.
+///
+/// Use this instead of sending `AppExit` yourself, which quits immediately.
+/// `AppExit::Success` is sent once the fade (`AudioFadeSettings::exit_fade_out`)
+/// has finished, so the app keeps running until then.
+#[derive(Event)]
+pub struct FadeOutAndExitEvent;
+
+struct Fade {
+    from: f64,
+    to: f64,
+    elapsed: f32,
+    duration: f32,
+    exit: bool,
+}
+
+/// The fade that is currently running, if any.
+#[derive(Resource, Default)]
+struct ActiveFade(Option<Fade>);
+
+impl ActiveFade {
+    fn start(&mut self, game_audio: &GameAudio, to: f64, duration: f32, exit: bool) {
+        self.0 = Some(Fade {
+            from: game_audio.fade,
+            to,
+            elapsed: 0.0,
+            duration,
+            exit,
+        });
+    }
+}
+
+fn start_startup_fade(
+    settings: Res<AudioFadeSettings>,
+    mut game_audio: ResMut<GameAudio>,
+    mut active_fade: ResMut<ActiveFade>,
+) {
+    if let Some(duration) = settings.startup_fade_in {
+        game_audio.fade = 0.0;
+        active_fade.start(&game_audio, 1.0, duration, false);
+    }
+}
+
+fn start_exit_fade(
+    settings: Res<AudioFadeSettings>,
+    game_audio: Res<GameAudio>,
+    mut active_fade: ResMut<ActiveFade>,
+) {
+    active_fade.start(&game_audio, 0.0, settings.exit_fade_out, true);
+}
+
+fn start_requested_fade(mut game_audio: ResMut<GameAudio>, mut active_fade: ResMut<ActiveFade>) {
+    // Only access the resource mutably if there is a request, to not trigger change detection.
+    let Some((to, duration)) = game_audio.requested_fade else {
+        return;
+    };
+    game_audio.requested_fade = None;
+    if active_fade.0.as_ref().is_some_and(|fade| fade.exit) {
+        return;
+    }
+    active_fade.start(&game_audio, to, duration, false);
+}
+
+fn update_fade(
+    time: Res<Time>,
+    mut game_audio: ResMut<GameAudio>,
+    mut active_fade: ResMut<ActiveFade>,
+    mut ev_app_exit: EventWriter<AppExit>,
+) {
+    let Some(fade) = &mut active_fade.0 else {
+        return;
+    };
+
+    fade.elapsed += time.delta_seconds();
+    let t = if fade.duration > 0.0 {
+        (fade.elapsed / fade.duration).min(1.0) as f64
+    } else {
+        1.0
+    };
+    game_audio.fade = fade.from + (fade.to - fade.from) * t;
+
+    if t >= 1.0 {
+        if fade.exit {
+            ev_app_exit.send(AppExit::Success);
+        }
+        active_fade.0 = None;
+    }
+}
+
+pub struct AudioFadePlugin;
+
+impl Plugin for AudioFadePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioFadeSettings>()
+            .init_resource::<ActiveFade>()
+            .add_event::<FadeOutAndExitEvent>()
+            .add_systems(Startup, start_startup_fade)
+            .add_systems(
+                Update,
+                (
+                    start_exit_fade.run_if(on_event::<FadeOutAndExitEvent>()),
+                    start_requested_fade,
+                    update_fade,
+                )
+                    .chain()
+                    .before(AudioSystem::PlaySound),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<GameAudio>()
+            .add_plugins(AudioFadePlugin)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                0.25,
+            )));
+        // The first update has a delta of zero.
+        app.update();
+        app
+    }
+
+    fn fade(app: &App) -> f64 {
+        app.world().resource::<GameAudio>().fade
+    }
+
+    #[test]
+    fn fades_global_volume_over_duration() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<GameAudio>()
+            .fade_global_volume_to(0.0, 1.0);
+
+        for expected in [0.75, 0.5, 0.25, 0.0, 0.0] {
+            app.update();
+            assert!((fade(&app) - expected).abs() < 1e-6);
+        }
+        assert_eq!(app.world().resource::<GameAudio>().global_volume(), 0.5);
+    }
+
+    #[test]
+    fn exits_after_fading_out() {
+        let mut app = app();
+        app.world_mut().send_event(FadeOutAndExitEvent);
+
+        app.update();
+        assert!((fade(&app) - 0.5).abs() < 1e-6);
+        assert!(app.world().resource::<Events<AppExit>>().is_empty());
+
+        app.update();
+        assert_eq!(fade(&app), 0.0);
+        assert!(!app.world().resource::<Events<AppExit>>().is_empty());
+    }
+
+    #[test]
+    fn fades_in_on_startup() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<GameAudio>()
+            .insert_resource(AudioFadeSettings {
+                startup_fade_in: Some(1.0),
+                ..default()
+            })
+            .add_plugins(AudioFadePlugin);
+
+        app.update();
+        assert_eq!(fade(&app), 0.0);
+    }
+}
@@ -1,40 +1,148 @@
+mod channel;
+mod fade;
+#[cfg(feature = "test-audio")]
+mod recorder;
 mod sound;
 mod spacial;
 
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 use bevy_kira_audio::prelude::*;
 
-pub use sound::PlaySound;
-pub use spacial::SpacialSound;
+pub use channel::{
+    AudioBus, PauseAudioEvent, PauseChannelEvent, ResumeAudioEvent, ResumeChannelEvent,
+    SoundChannel,
+};
+pub use fade::{AudioFadeSettings, FadeOutAndExitEvent};
+#[cfg(feature = "test-audio")]
+pub use recorder::{AudioRecorder, RecordedAudioCommand};
+pub use sound::{AudioStats, PlaySound, PlaySpacialSound, SoundId, StopSound};
+pub use spacial::{
+    SpacialAttenuation, SpacialListenerOverride, SpacialPanning, SpacialRange, SpacialSound,
+    SpacialSoundBuilder,
+};
 
 const DEFAULT_VOLUME: f64 = 0.5;
 const MAX_SPACIAL_DISTANCE: f64 = 250.0;
+/// Exponent of the curve that maps slider positions to volumes.
+const VOLUME_CURVE_EXPONENT: f64 = 2.5;
 
 pub struct GameAudioPlugin;
 
 impl Plugin for GameAudioPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(AudioPlugin)
-            .add_plugins((spacial::SpacialAudioPlugin, sound::GameSoundPlugin))
-            .init_resource::<GameAudio>();
+        // Adding kira's plugin twice would panic, so reuse it if the game already added it.
+        if app.is_plugin_added::<AudioPlugin>() {
+            #[cfg(debug_assertions)]
+            warn!("`AudioPlugin` was already added, `GameAudioPlugin` will reuse it");
+        } else {
+            app.add_plugins(AudioPlugin);
+        }
+
+        app.configure_sets(
+            Update,
+            (
+                AudioSystem::PlaySound,
+                AudioSystem::SpacialUpdate,
+                AudioSystem::Cleanup,
+            )
+                .chain(),
+        )
+        .add_plugins((
+            spacial::SpacialAudioPlugin,
+            sound::GameSoundPlugin,
+            channel::SoundChannelPlugin,
+            fade::AudioFadePlugin,
+        ))
+        .init_resource::<GameAudio>();
+
+        #[cfg(feature = "test-audio")]
+        app.init_resource::<AudioRecorder>();
     }
 }
 
+/// Sets that are used to play and update the game audio.
+/// They run in `Update`, in the order they are listed here.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AudioSystem {
+    /// Set in which `PlaySound` events are played
+    /// and channels get paused or resumed.
+    /// Send your `PlaySound` events before this set.
+    PlaySound,
+    /// Set that will update the volumes of all spacial sounds.
+    /// Spawn your spacial emitters before this set,
+    /// otherwise they will play with the wrong volume for one frame.
+    SpacialUpdate,
+    /// Set in which stopped instances are removed from the `AudioEmitter`s.
+    /// This runs after `SpacialUpdate`, so the volumes are updated first
+    /// and a sound that stopped this frame is removed right after.
+    Cleanup,
+}
+
 /// Global properties for all audio clips.
-#[derive(Resource)]
+///
+/// All sounds are played on `bevy_kira_audio`'s main channel, the `Audio` resource.
+/// You can use it directly if you need something that this crate doesn't wrap,
+/// for example to stop every sound with `Audio::stop`.
+/// Keep in mind that the volume of every instance is set by this crate
+/// (`volume * channel volume * global_volume`, attenuated for spacial sounds),
+/// so volume changes applied to the channel itself will be overwritten.
+/// Use `global_volume` as the master volume instead.
+#[derive(Resource, Debug)]
 pub struct GameAudio {
     /// The volume that all sounds will be multiplied by.
     global_volume: f64,
+    muted: bool,
+    /// Multiplier of the global volume that is driven by the `AudioFadeSettings`
+    /// and `fade_global_volume_to`.
+    fade: f64,
+    /// The target and duration of a `fade_global_volume_to` that hasn't started yet.
+    requested_fade: Option<(f64, f32)>,
     /// The maximum distance for any spacial audio.
     /// Any sounds further away than this value will be muted.
     pub max_spacial_distance: f64,
+    /// How the audible range of spacial sounds is determined,
+    /// a fixed world distance by default.
+    pub spacial_range: SpacialRange,
+    /// How the volume of spacial sounds falls off with the distance.
+    pub spacial_attenuation: SpacialAttenuation,
+    spacial_distance_scale: f64,
+    /// How spacial sounds are panned, off by default.
+    pub spacial_panning: SpacialPanning,
+    /// The maximum number of audible spacial instances, unlimited by default.
+    /// If more instances are audible, the quietest (most attenuated) ones are paused
+    /// until enough of the louder ones stop or move away, then they resume.
+    /// Useful to keep the mix clean in busy areas.
+    pub max_spacial_voices: Option<usize>,
+    /// How strong the Doppler effect of moving spacial sounds is, `0.0` (off) by default.
+    /// With `1.0` the pitch shift is roughly physically accurate,
+    /// assuming a speed of sound of `1000.0` world units per second.
+    /// The shift is clamped to at most double or half the playback rate.
+    pub doppler_intensity: f64,
+    paused_channels: HashSet<SoundChannel>,
+    channel_volumes: HashMap<SoundChannel, f64>,
+    bus_volumes: HashMap<AudioBus, f64>,
 }
 
 impl Default for GameAudio {
     fn default() -> Self {
         Self {
             global_volume: DEFAULT_VOLUME,
+            muted: false,
+            fade: 1.0,
+            requested_fade: None,
             max_spacial_distance: MAX_SPACIAL_DISTANCE,
+            spacial_range: SpacialRange::default(),
+            spacial_attenuation: SpacialAttenuation::default(),
+            spacial_distance_scale: 1.0,
+            spacial_panning: SpacialPanning::default(),
+            max_spacial_voices: None,
+            doppler_intensity: 0.0,
+            paused_channels: HashSet::new(),
+            channel_volumes: HashMap::new(),
+            bus_volumes: HashMap::new(),
         }
     }
 }
@@ -60,4 +168,218 @@ impl GameAudio {
     pub fn set_global_volume(&mut self, volume: f64) {
         self.set_global_volume_clamped(volume);
     }
+
+    /// Mute or unmute all sounds, without changing the global volume.
+    /// Like changes of the global volume, this applies to sounds that are already playing
+    /// as well as to sounds that are played afterwards.
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    /// Whether all sounds are muted, see `set_muted`.
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Whether every channel is paused, for example after a `PauseAudioEvent`.
+    /// Use `is_channel_paused` to check a single channel.
+    pub fn is_paused(&self) -> bool {
+        SoundChannel::ALL
+            .iter()
+            .all(|channel| self.is_channel_paused(*channel))
+    }
+
+    /// The volume that sounds are actually multiplied by,
+    /// taking mute and fades into account.
+    fn output_volume(&self) -> f64 {
+        if self.muted {
+            0.0
+        } else {
+            self.global_volume * self.fade
+        }
+    }
+
+    /// The volume of the given channel, `1.0` unless changed.
+    pub fn channel_volume(&self, channel: SoundChannel) -> f64 {
+        self.channel_volumes.get(&channel).copied().unwrap_or(1.0)
+    }
+
+    /// Set the volume of the given channel, which all sounds on it are multiplied by.
+    /// The volume will always be clamped between `0.0..1.0`.
+    pub fn set_channel_volume(&mut self, channel: SoundChannel, volume: f64) {
+        self.channel_volumes.insert(channel, volume.clamp(0.0, 1.0));
+    }
+
+    /// Smoothly change the effective volume to `target` times the global volume
+    /// over `duration` seconds, for example to muffle everything in an underwater area.
+    /// Use `1.0` to fade back to the full global volume.
+    ///
+    /// This doesn't touch the global volume itself, which is the player's setting,
+    /// it fades the same multiplier as the `AudioFadeSettings`.
+    /// It is ignored while the fade out of `FadeOutAndExitEvent` is running.
+    pub fn fade_global_volume_to(&mut self, target: f64, duration: f32) {
+        self.requested_fade = Some((target.clamp(0.0, 1.0), duration));
+    }
+
+    /// The volume of the given bus, `1.0` if it was never set.
+    pub fn bus_volume(&self, bus: AudioBus) -> f64 {
+        self.bus_volumes.get(&bus).copied().unwrap_or(1.0)
+    }
+
+    /// Set the volume of the given bus, which all sounds on it are multiplied by.
+    /// The volume will always be clamped between `0.0..1.0`.
+    pub fn set_bus_volume(&mut self, bus: AudioBus, volume: f64) {
+        self.bus_volumes.insert(bus, volume.clamp(0.0, 1.0));
+    }
+
+    /// The volume that sounds on the given channel are currently multiplied by,
+    /// before their own volume and spacial attenuation. Useful for metering.
+    ///
+    /// This is the channel volume multiplied by the global volume
+    /// and the fade (see `AudioFadeSettings`),
+    /// and `0.0` if the audio is muted or the channel is paused.
+    pub fn effective_channel_volume(&self, channel: SoundChannel) -> f64 {
+        if self.is_channel_paused(channel) {
+            0.0
+        } else {
+            self.channel_output_volume(channel)
+        }
+    }
+
+    /// The volume that sounds on the given channel are multiplied by,
+    /// which is the same for spacial and non-spacial sounds.
+    /// Paused channels are ignored here, they are paused instead of silenced.
+    fn channel_output_volume(&self, channel: SoundChannel) -> f64 {
+        self.channel_volume(channel) * self.output_volume()
+    }
+
+    /// The channel output volume multiplied by the volume of the bus, if any.
+    fn sound_output_volume(&self, channel: SoundChannel, bus: Option<AudioBus>) -> f64 {
+        self.channel_output_volume(channel) * bus.map_or(1.0, |bus| self.bus_volume(bus))
+    }
+
+    /// The distance at which spacial sounds become inaudible,
+    /// taking `spacial_range` into account.
+    pub fn spacial_distance(&self) -> f64 {
+        self.max_spacial_distance * self.spacial_distance_scale
+    }
+
+    /// Convert the position of a volume slider (`0.0..1.0`) to a volume
+    /// that sounds perceptually even, using `position^2.5`.
+    /// Use this for options menus, a linear slider feels wrong
+    /// because we perceive loudness logarithmically.
+    pub fn volume_from_slider(position: f64) -> f64 {
+        position.clamp(0.0, 1.0).powf(VOLUME_CURVE_EXPONENT)
+    }
+
+    /// Convert a volume (`0.0..1.0`) to the position of a volume slider,
+    /// the inverse of `volume_from_slider`.
+    pub fn slider_from_volume(volume: f64) -> f64 {
+        volume.clamp(0.0, 1.0).powf(VOLUME_CURVE_EXPONENT.recip())
+    }
+
+    /// Whether the given channel is currently paused.
+    /// Use `PauseChannelEvent` and `ResumeChannelEvent` to change this.
+    pub fn is_channel_paused(&self, channel: SoundChannel) -> bool {
+        self.paused_channels.contains(&channel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn muting_keeps_global_volume() {
+        let mut game_audio = GameAudio::default();
+        game_audio.set_muted(true);
+
+        assert!(game_audio.is_muted());
+        assert_eq!(game_audio.global_volume(), DEFAULT_VOLUME);
+        for channel in SoundChannel::ALL {
+            assert_eq!(game_audio.effective_channel_volume(channel), 0.0);
+        }
+
+        game_audio.set_muted(false);
+        assert!(!game_audio.is_muted());
+        assert_eq!(
+            game_audio.effective_channel_volume(SoundChannel::Sfx),
+            DEFAULT_VOLUME
+        );
+    }
+
+    #[test]
+    fn paused_only_if_every_channel_is_paused() {
+        let mut game_audio = GameAudio::default();
+        game_audio.paused_channels.insert(SoundChannel::Music);
+        assert!(!game_audio.is_paused());
+
+        game_audio.paused_channels.extend(SoundChannel::ALL);
+        assert!(game_audio.is_paused());
+    }
+
+    #[test]
+    fn bus_volumes_compose_with_channels() {
+        let mut game_audio = GameAudio::default();
+        let weapons = AudioBus("weapons");
+        assert_eq!(game_audio.bus_volume(weapons), 1.0);
+
+        game_audio.set_bus_volume(weapons, 2.0);
+        assert_eq!(game_audio.bus_volume(weapons), 1.0);
+        game_audio.set_bus_volume(weapons, 0.5);
+        game_audio.set_channel_volume(SoundChannel::Sfx, 0.5);
+
+        let global = game_audio.global_volume();
+        assert_eq!(
+            game_audio.sound_output_volume(SoundChannel::Sfx, Some(weapons)),
+            global * 0.25
+        );
+        // Other buses are unaffected.
+        assert_eq!(
+            game_audio.sound_output_volume(SoundChannel::Sfx, Some(AudioBus("voices"))),
+            global * 0.5
+        );
+    }
+
+    #[test]
+    fn sound_volume_is_base_times_channel_times_global() {
+        let mut game_audio = GameAudio::default();
+        game_audio.set_global_volume(0.8);
+        game_audio.set_channel_volume(SoundChannel::Music, 0.5);
+        game_audio.set_bus_volume(AudioBus("ambience"), 0.25);
+
+        let base = 0.6;
+        assert!(
+            (base * game_audio.sound_output_volume(SoundChannel::Music, None) - 0.6 * 0.5 * 0.8)
+                .abs()
+                < 1e-9
+        );
+        assert!(
+            (game_audio.sound_output_volume(SoundChannel::Music, Some(AudioBus("ambience")))
+                - 0.5 * 0.8 * 0.25)
+                .abs()
+                < 1e-9
+        );
+        assert_eq!(
+            game_audio.sound_output_volume(SoundChannel::Sfx, None),
+            game_audio.effective_channel_volume(SoundChannel::Sfx)
+        );
+    }
+
+    #[test]
+    fn volume_slider_curve() {
+        assert_eq!(GameAudio::volume_from_slider(0.0), 0.0);
+        assert_eq!(GameAudio::volume_from_slider(1.0), 1.0);
+        assert!((GameAudio::volume_from_slider(0.5) - 0.5f64.powf(2.5)).abs() < 1e-12);
+        assert!(GameAudio::volume_from_slider(0.5) < 0.5);
+        assert_eq!(GameAudio::slider_from_volume(0.0), 0.0);
+        assert_eq!(GameAudio::slider_from_volume(1.0), 1.0);
+        assert!(GameAudio::slider_from_volume(0.5) > 0.5);
+
+        for i in 0..=20 {
+            let position = i as f64 / 20.0;
+            let volume = GameAudio::volume_from_slider(position);
+            assert!((GameAudio::slider_from_volume(volume) - position).abs() < 1e-9);
+        }
+    }
 }
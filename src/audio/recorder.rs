@@ -0,0 +1,159 @@
+use bevy::prelude::*;
+use bevy_kira_audio::prelude::*;
+
+use super::{channel::SoundChannel, sound::SoundId};
+
+/// A command that the audio systems sent to `bevy_kira_audio`, see `AudioRecorder`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordedAudioCommand {
+    /// A sound was played.
+    Play {
+        /// The handle of the new instance.
+        instance: Handle<AudioInstance>,
+        /// The channel the sound was played on.
+        channel: SoundChannel,
+        /// The volume the sound was played with,
+        /// which already includes the channel, bus and global volume.
+        volume: f64,
+        /// Whether the sound started paused, because its channel is paused.
+        paused: bool,
+    },
+    /// The volume of a playing sound was set.
+    /// Spacial sounds get one of these every frame, with their attenuated volume.
+    SetVolume {
+        /// The handle of the changed instance.
+        instance: Handle<AudioInstance>,
+        /// The new volume, including the channel, bus and global volume.
+        volume: f64,
+    },
+    /// The sounds with the given `SoundId` were stopped, see `StopSound`.
+    Stop {
+        /// The id of the stopped sounds.
+        id: SoundId,
+        /// Whether they were stopped without a fade out.
+        immediate: bool,
+    },
+    /// The given channel was paused or resumed.
+    SetChannelPaused {
+        /// The paused or resumed channel.
+        channel: SoundChannel,
+        /// `true` if it was paused, `false` if it was resumed.
+        paused: bool,
+    },
+}
+
+/// Records the commands that the audio systems send to `bevy_kira_audio`,
+/// only available with the `test-audio` feature.
+///
+/// Without an audio device `bevy_kira_audio` never creates the `AudioInstance`s,
+/// so the volumes and pauses can't be checked on them in headless tests.
+/// Instead, every command is recorded here, even if the instance doesn't exist.
+/// The volume of spacial sounds is updated every frame, so `take` the commands
+/// before the frame you are interested in.
+///
+/// ```ignore
+/// app.add_plugins((MinimalPlugins, AssetPlugin::default(), GameAudioPlugin));
+/// app.world_mut().send_event(PauseChannelEvent(SoundChannel::Music));
+/// app.update();
+///
+/// let recorder = app.world_mut().resource_mut::<AudioRecorder>().take();
+/// assert!(recorder.contains(&RecordedAudioCommand::SetChannelPaused {
+///     channel: SoundChannel::Music,
+///     paused: true,
+/// }));
+/// ```
+#[derive(Resource, Default, Debug)]
+pub struct AudioRecorder(Vec<RecordedAudioCommand>);
+
+impl AudioRecorder {
+    pub(super) fn record(&mut self, command: RecordedAudioCommand) {
+        self.0.push(command);
+    }
+
+    /// All commands recorded since the last `take`, oldest first.
+    pub fn commands(&self) -> &[RecordedAudioCommand] {
+        &self.0
+    }
+
+    /// Remove and return all recorded commands.
+    pub fn take(&mut self) -> Vec<RecordedAudioCommand> {
+        std::mem::take(&mut self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::gizmos::gizmos::GizmoStorage;
+    use bevy_kira_audio::prelude::AudioSource;
+    use bevy_rapier2d::render::DebugRenderContext;
+
+    use super::*;
+    use crate::{
+        audio::{GameAudio, GameAudioPlugin, PauseChannelEvent, PlaySound, StopSound},
+        utils::debug::DebugPlugin,
+    };
+
+    fn app() -> App {
+        let mut gizmo_config = GizmoConfigStore::default();
+        gizmo_config.insert(GizmoConfig::default(), DefaultGizmoConfigGroup);
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .insert_resource(gizmo_config)
+            .init_resource::<DebugRenderContext>()
+            .init_resource::<GizmoStorage<DefaultGizmoConfigGroup, ()>>()
+            .add_plugins((DebugPlugin, GameAudioPlugin));
+        app
+    }
+
+    #[test]
+    fn records_audio_commands() {
+        let mut app = app();
+        app.world_mut().send_event(PlaySound {
+            clip: Handle::<AudioSource>::weak_from_u128(7),
+            volume: 0.5,
+            channel: SoundChannel::Music,
+            id: Some(SoundId(1)),
+            ..default()
+        });
+        app.update();
+
+        let commands = app.world_mut().resource_mut::<AudioRecorder>().take();
+        let [RecordedAudioCommand::Play {
+            instance,
+            channel: SoundChannel::Music,
+            volume,
+            paused: false,
+        }] = commands.as_slice()
+        else {
+            panic!("expected a single play command, got {commands:?}");
+        };
+        assert_eq!(*volume, 0.5 * GameAudio::default().global_volume());
+        let instance = instance.clone();
+
+        app.world_mut()
+            .resource_mut::<GameAudio>()
+            .set_global_volume(1.0);
+        app.world_mut()
+            .send_event(PauseChannelEvent(SoundChannel::Music));
+        app.world_mut().send_event(StopSound {
+            id: SoundId(1),
+            immediate: true,
+        });
+        app.update();
+
+        let commands = app.world_mut().resource_mut::<AudioRecorder>().take();
+        assert!(commands.contains(&RecordedAudioCommand::SetVolume {
+            instance,
+            volume: 0.5,
+        }));
+        assert!(commands.contains(&RecordedAudioCommand::SetChannelPaused {
+            channel: SoundChannel::Music,
+            paused: true,
+        }));
+        assert!(commands.contains(&RecordedAudioCommand::Stop {
+            id: SoundId(1),
+            immediate: true,
+        }));
+    }
+}
@@ -1,20 +1,123 @@
+use std::time::Duration;
+
 use rand::{thread_rng, Rng};
 
 use bevy::{prelude::*, utils::HashSet};
 use bevy_kira_audio::prelude::{AudioSource, *};
 
-use super::{spacial::SpacialSound, GameAudio};
+use crate::camera::MainCamera;
+
+use super::{
+    channel::{AudioBus, SoundChannel},
+    spacial::{
+        listener_transform, SoloSpacialEmitter, SpacialListenerOverride, SpacialMix, SpacialSound,
+    },
+    AudioFadeSettings, AudioSystem, GameAudio,
+};
+#[cfg(feature = "test-audio")]
+use super::{AudioRecorder, RecordedAudioCommand};
+
+/// How often finished sounds are removed from `PlayingSounds`, in seconds.
+const SOUND_CLEANUP_INTERVAL: f32 = 1.0;
+/// How often the `AudioStats` are updated, in seconds.
+const AUDIO_STATS_INTERVAL: f32 = 0.25;
+
+pub(super) struct PlayingSound {
+    id: Option<SoundId>,
+    pub(super) channel: SoundChannel,
+    bus: Option<AudioBus>,
+    volume: f64,
+    spacial: bool,
+    pub(super) handle: Handle<AudioInstance>,
+}
 
+/// All sounds that were played with `PlaySound`.
 #[derive(Resource, Deref, DerefMut, Default)]
-struct RepeatingSounds(Vec<(f64, Handle<AudioInstance>)>);
+pub(super) struct PlayingSounds(Vec<PlayingSound>);
+
+impl PlayingSounds {
+    /// Remove the sounds whose instance has stopped.
+    /// `state` returns the state of an instance, or `None` if it doesn't exist.
+    fn remove_stopped(&mut self, state: impl Fn(&Handle<AudioInstance>) -> Option<PlaybackState>) {
+        // Sounds whose instance doesn't exist yet haven't started,
+        // `bevy_kira_audio` keeps retrying to play them while their clip is loading.
+        self.retain(|sound| {
+            state(&sound.handle).is_none_or(|state| state != PlaybackState::Stopped)
+        });
+    }
+
+    /// The number of sounds whose instance is playing, see `remove_stopped` for the `state`.
+    fn count_playing(
+        &self,
+        state: impl Fn(&Handle<AudioInstance>) -> Option<PlaybackState>,
+    ) -> usize {
+        self.iter()
+            .filter(|sound| matches!(state(&sound.handle), Some(PlaybackState::Playing { .. })))
+            .count()
+    }
+}
+
+/// Statistics about the played sounds, updated four times per second.
+/// Useful to catch voice leaks and runaway spawns, for example in a perf HUD.
+/// In debug mode they are shown in an overlay, see `DebugFlags::audio_stats`.
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct AudioStats {
+    /// The number of sounds (spacial and non-spacial) that are currently playing.
+    /// Paused sounds are not counted.
+    pub playing: usize,
+    /// The highest `playing` count since startup.
+    pub peak_playing: usize,
+}
+
+impl AudioStats {
+    fn set_playing(&mut self, playing: usize) {
+        self.playing = playing;
+        self.peak_playing = self.peak_playing.max(playing);
+    }
+}
+
+/// An identifier you choose for a sound, to stop it later with `StopSound`.
+/// Multiple sounds can share the same id, they are then stopped together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SoundId(pub u64);
+
+/// Send this `Event` to stop all playing sounds with the given id.
+#[derive(Event)]
+pub struct StopSound {
+    /// The id that was given to `PlaySound::id`.
+    pub id: SoundId,
+    /// Stop the sound immediately instead of fading it out
+    /// over `AudioFadeSettings::stop_fade_out`.
+    /// Cutting off a sound mid-waveform can cause an audible click,
+    /// so only use this if even a short fade is too slow.
+    pub immediate: bool,
+}
+
+impl StopSound {
+    /// How long the sound fades out before it stops.
+    fn fade_out(&self, settings: &AudioFadeSettings) -> Duration {
+        if self.immediate {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f32(settings.stop_fade_out)
+        }
+    }
+}
 
 /// Send this `Event` to play an audio sound.
 /// It has various properties to allow for different kinds of audio clips.
+///
+/// Every played sound is tracked (so that it can be paused for example)
+/// until it stops. Stopped sounds are cleaned up periodically,
+/// so playing thousands of sounds in a long session won't leak memory.
 #[derive(Event)]
 pub struct PlaySound {
     /// The asset of the audio to play.
     pub clip: Handle<AudioSource>,
-    /// Volume of the sound to play.
+    /// Base volume of the sound to play.
+    /// The final volume is `volume * bus volume * channel volume * global volume`,
+    /// additionally attenuated by the distance for spacial sounds.
+    /// Changes of the channel and global volume also apply while the sound is playing.
     pub volume: f64,
     /// Playback rate, default is 1.0.
     pub playback_rate: f64,
@@ -25,11 +128,58 @@ pub struct PlaySound {
     /// Whether to repeat the sound.
     pub repeat: bool,
     /// Whether to reverse the sound.
+    /// All clips are loaded completely into memory by `bevy_kira_audio`,
+    /// so this works for every supported format.
     pub reverse: bool,
+    /// Start playing the sound this many seconds into the clip,
+    /// for example to start a long clip mid-way. `None` starts at the beginning.
+    pub start_position: Option<f64>,
     /// If you want to have spacial audio, you must give a parent entity.
     pub parent: Option<Entity>,
+    /// The channel to play the sound on.
+    pub channel: SoundChannel,
+    /// The bus to play the sound on, if any.
+    pub bus: Option<AudioBus>,
+    /// An id to stop the sound later with `StopSound`.
+    pub id: Option<SoundId>,
+}
+
+/// Send this `Event` to play a fire-and-forget positional sound,
+/// for example an explosion at a world point.
+///
+/// This spawns a temporary spacial emitter at the given position
+/// that gets despawned once the sound has finished.
+/// Use `PlaySound` with a `parent` if the sound should follow an entity.
+#[derive(Event)]
+pub struct PlaySpacialSound {
+    /// The asset of the audio to play.
+    pub clip: Handle<AudioSource>,
+    /// The world position to play the sound at.
+    pub position: Vec2,
+    /// Volume of the sound to play.
+    pub volume: f64,
+    /// The channel to play the sound on.
+    pub channel: SoundChannel,
+    /// The bus to play the sound on, if any.
+    pub bus: Option<AudioBus>,
 }
 
+impl Default for PlaySpacialSound {
+    fn default() -> Self {
+        Self {
+            clip: Handle::default(),
+            position: Vec2::ZERO,
+            volume: 1.0,
+            channel: SoundChannel::default(),
+            bus: None,
+        }
+    }
+}
+
+/// Marker for emitters spawned by `PlaySpacialSound`.
+#[derive(Component)]
+struct TemporaryEmitter;
+
 impl Default for PlaySound {
     fn default() -> Self {
         Self {
@@ -39,18 +189,38 @@ impl Default for PlaySound {
             rand_speed_intensity: 0.0,
             repeat: false,
             reverse: false,
+            start_position: None,
             parent: None,
+            channel: SoundChannel::default(),
+            bus: None,
+            id: None,
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn play_sounds(
     mut commands: Commands,
     audio: Res<Audio>,
     game_audio: Res<GameAudio>,
-    mut repeating_sounds: ResMut<RepeatingSounds>,
+    mut playing_sounds: ResMut<PlayingSounds>,
+    listener_override: Res<SpacialListenerOverride>,
+    solo: Res<SoloSpacialEmitter>,
+    q_transforms: Query<&GlobalTransform>,
+    q_receiver: Query<&GlobalTransform, With<AudioReceiver>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    q_emitters: Query<(Entity, &GlobalTransform, &AudioEmitter, &SpacialSound)>,
     mut ev_play_sound: EventReader<PlaySound>,
+    #[cfg(feature = "test-audio")] mut recorder: ResMut<AudioRecorder>,
 ) {
+    let mix = listener_transform(&listener_override, &q_receiver)
+        .ok()
+        .map(|receiver| SpacialMix {
+            game_audio: &game_audio,
+            receiver,
+            camera: q_camera.get_single().ok(),
+            solo: solo.0,
+        });
     let mut rng = thread_rng();
     let mut added_sounds: HashSet<Handle<AudioSource>> = HashSet::new();
 
@@ -65,29 +235,69 @@ fn play_sounds(
         } else {
             rng.gen_range(-1.0..1.0) * ev.rand_speed_intensity
         };
-        let volume_offset = if ev.parent.is_some() { 0.0 } else { 1.0 };
+        let spacial_sound = SpacialSound::new(ev.volume)
+            .with_channel(ev.channel)
+            .with_bus(ev.bus)
+            .with_playback_rate(ev.playback_rate + speed_offset);
+        // Spacial sounds get their final volume right away,
+        // otherwise they would play at the wrong volume until the next spacial update.
+        // The emitter is a child without offset, so it has the transform of the parent.
+        let (volume, panning) = match ev.parent {
+            Some(parent) => match (q_transforms.get(parent), &mix) {
+                (Ok(emitter), Some(mix)) => {
+                    mix.new_emitter_output(emitter, &spacial_sound, &q_emitters)
+                }
+                _ => (0.0, None),
+            },
+            None => (
+                ev.volume * game_audio.sound_output_volume(ev.channel, ev.bus),
+                None,
+            ),
+        };
 
         let mut audio_command = audio.play(ev.clip.clone());
-        let sound_volume = ev.volume * volume_offset;
         audio_command
-            .with_volume(sound_volume * game_audio.global_volume)
+            .with_volume(volume)
             .with_playback_rate(ev.playback_rate + speed_offset);
+        if let Some(panning) = panning {
+            audio_command.with_panning(panning);
+        }
 
         let audio_instance = audio_command.handle();
+        playing_sounds.push(PlayingSound {
+            id: ev.id,
+            channel: ev.channel,
+            bus: ev.bus,
+            volume: ev.volume,
+            spacial: ev.parent.is_some(),
+            handle: audio_instance.clone(),
+        });
 
         if ev.repeat {
             audio_command.looped();
-            repeating_sounds.push((sound_volume, audio_instance.clone()));
         }
         if ev.reverse {
             audio_command.reverse();
         }
+        if let Some(start_position) = ev.start_position {
+            audio_command.start_from(start_position);
+        }
+        if game_audio.is_channel_paused(ev.channel) {
+            audio_command.paused();
+        }
+        #[cfg(feature = "test-audio")]
+        recorder.record(RecordedAudioCommand::Play {
+            instance: audio_instance.clone(),
+            channel: ev.channel,
+            volume,
+            paused: game_audio.is_channel_paused(ev.channel),
+        });
 
         if let Some(parent) = ev.parent {
             let audio_emitter = commands
                 .spawn((
                     TransformBundle::default(),
-                    SpacialSound::new(ev.volume),
+                    spacial_sound,
                     AudioEmitter {
                         instances: vec![audio_instance],
                     },
@@ -106,42 +316,344 @@ fn play_sounds(
     }
 }
 
-fn update_repeating_sounds(
-    mut audio_instances: ResMut<Assets<AudioInstance>>,
+#[allow(clippy::too_many_arguments)]
+fn play_spacial_sounds(
+    mut commands: Commands,
+    audio: Res<Audio>,
     game_audio: Res<GameAudio>,
-    mut repeating_sounds: ResMut<RepeatingSounds>,
+    mut playing_sounds: ResMut<PlayingSounds>,
+    listener_override: Res<SpacialListenerOverride>,
+    solo: Res<SoloSpacialEmitter>,
+    q_receiver: Query<&GlobalTransform, With<AudioReceiver>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    q_emitters: Query<(Entity, &GlobalTransform, &AudioEmitter, &SpacialSound)>,
+    mut ev_play_spacial_sound: EventReader<PlaySpacialSound>,
+    #[cfg(feature = "test-audio")] mut recorder: ResMut<AudioRecorder>,
 ) {
-    let mut invalid_indices = vec![];
-    for (index, (volume, instance)) in repeating_sounds.iter().enumerate() {
-        match audio_instances.get_mut(instance) {
-            Some(r) => {
-                r.set_volume(volume * game_audio.global_volume, AudioTween::default());
-            }
-            None => {
-                invalid_indices.push(index);
+    let mix = listener_transform(&listener_override, &q_receiver)
+        .ok()
+        .map(|receiver| SpacialMix {
+            game_audio: &game_audio,
+            receiver,
+            camera: q_camera.get_single().ok(),
+            solo: solo.0,
+        });
+
+    for ev in ev_play_spacial_sound.read() {
+        let transform = Transform::from_translation(ev.position.extend(0.0));
+        let spacial_sound = SpacialSound::new(ev.volume)
+            .with_channel(ev.channel)
+            .with_bus(ev.bus);
+        let (volume, panning) = match &mix {
+            Some(mix) => mix.new_emitter_output(&transform.into(), &spacial_sound, &q_emitters),
+            None => (0.0, None),
+        };
+
+        let mut audio_command = audio.play(ev.clip.clone());
+        audio_command.with_volume(volume);
+        if let Some(panning) = panning {
+            audio_command.with_panning(panning);
+        }
+        if game_audio.is_channel_paused(ev.channel) {
+            audio_command.paused();
+        }
+
+        let audio_instance = audio_command.handle();
+        #[cfg(feature = "test-audio")]
+        recorder.record(RecordedAudioCommand::Play {
+            instance: audio_instance.clone(),
+            channel: ev.channel,
+            volume,
+            paused: game_audio.is_channel_paused(ev.channel),
+        });
+        playing_sounds.push(PlayingSound {
+            id: None,
+            channel: ev.channel,
+            bus: ev.bus,
+            volume: ev.volume,
+            spacial: true,
+            handle: audio_instance.clone(),
+        });
+
+        commands.spawn((
+            TransformBundle::from_transform(transform),
+            spacial_sound,
+            AudioEmitter {
+                instances: vec![audio_instance],
+            },
+            TemporaryEmitter,
+        ));
+    }
+}
+
+fn stop_sounds(
+    settings: Res<AudioFadeSettings>,
+    playing_sounds: Res<PlayingSounds>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut ev_stop_sound: EventReader<StopSound>,
+    #[cfg(feature = "test-audio")] mut recorder: ResMut<AudioRecorder>,
+) {
+    for ev in ev_stop_sound.read() {
+        #[cfg(feature = "test-audio")]
+        recorder.record(RecordedAudioCommand::Stop {
+            id: ev.id,
+            immediate: ev.immediate,
+        });
+        let duration = ev.fade_out(&settings);
+        for sound in playing_sounds
+            .iter()
+            .filter(|sound| sound.id == Some(ev.id))
+        {
+            if let Some(instance) = audio_instances.get_mut(&sound.handle) {
+                instance.stop(AudioTween::linear(duration));
             }
         }
     }
+}
 
-    for index in invalid_indices.iter().rev() {
-        repeating_sounds.remove(*index);
+/// Despawn the emitters of `PlaySpacialSound` once all their instances have stopped.
+/// Stopped instances are removed from the emitters in `AudioSystem::Cleanup`,
+/// so this must run after that.
+fn despawn_finished_temporary_emitters(
+    mut commands: Commands,
+    q_emitters: Query<(Entity, &AudioEmitter), With<TemporaryEmitter>>,
+) {
+    for (entity, emitter) in &q_emitters {
+        if emitter.instances.is_empty() {
+            commands.entity(entity).despawn_recursive();
+        }
     }
 }
 
+/// Apply changes of the `GameAudio` volumes to all non-spacial sounds that are playing.
+/// Spacial sounds are updated with their attenuation in `AudioSystem::SpacialUpdate`.
+fn update_playing_sound_volumes(
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    game_audio: Res<GameAudio>,
+    playing_sounds: Res<PlayingSounds>,
+    #[cfg(feature = "test-audio")] mut recorder: ResMut<AudioRecorder>,
+) {
+    for sound in playing_sounds.iter().filter(|sound| !sound.spacial) {
+        let volume = sound.volume * game_audio.sound_output_volume(sound.channel, sound.bus);
+        #[cfg(feature = "test-audio")]
+        recorder.record(RecordedAudioCommand::SetVolume {
+            instance: sound.handle.clone(),
+            volume,
+        });
+        if let Some(instance) = audio_instances.get_mut(&sound.handle) {
+            instance.set_volume(volume, AudioTween::default());
+        }
+    }
+}
+
+fn cleanup_stopped_sounds(
+    time: Res<Time>,
+    audio_instances: Res<Assets<AudioInstance>>,
+    mut playing_sounds: ResMut<PlayingSounds>,
+    mut since_last_cleanup: Local<f32>,
+) {
+    *since_last_cleanup += time.delta_seconds();
+    if *since_last_cleanup < SOUND_CLEANUP_INTERVAL {
+        return;
+    }
+    *since_last_cleanup = 0.0;
+
+    playing_sounds.remove_stopped(|handle| audio_instances.get(handle).map(AudioInstance::state));
+}
+
+fn update_audio_stats(
+    // The real time, so the stats keep updating while the game is paused.
+    time: Res<Time<Real>>,
+    audio_instances: Res<Assets<AudioInstance>>,
+    playing_sounds: Res<PlayingSounds>,
+    mut audio_stats: ResMut<AudioStats>,
+    mut since_last_update: Local<f32>,
+) {
+    *since_last_update += time.delta_seconds();
+    if *since_last_update < AUDIO_STATS_INTERVAL {
+        return;
+    }
+    *since_last_update = 0.0;
+
+    let playing = playing_sounds
+        .count_playing(|handle| audio_instances.get(handle).map(AudioInstance::state));
+    audio_stats.set_playing(playing);
+}
+
 pub struct GameSoundPlugin;
 
 impl Plugin for GameSoundPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<PlaySound>()
+            .add_event::<StopSound>()
+            .add_event::<PlaySpacialSound>()
             .init_resource::<GameAudio>()
-            .init_resource::<RepeatingSounds>()
+            .init_resource::<PlayingSounds>()
+            .init_resource::<AudioStats>()
             .add_systems(
                 Update,
                 (
-                    update_repeating_sounds.run_if(resource_changed::<GameAudio>),
+                    update_playing_sound_volumes.run_if(resource_changed::<GameAudio>),
+                    cleanup_stopped_sounds,
                     play_sounds,
+                    play_spacial_sounds,
+                    stop_sounds.run_if(on_event::<StopSound>()),
+                )
+                    .chain()
+                    .in_set(AudioSystem::PlaySound),
+            )
+            .add_systems(
+                Update,
+                (
+                    despawn_finished_temporary_emitters
+                        .after(super::spacial::cleanup_stopped_spacial_instances),
+                    update_audio_stats,
                 )
-                    .chain(),
+                    .in_set(AudioSystem::Cleanup),
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{gizmos::gizmos::GizmoStorage, time::TimeUpdateStrategy, utils::HashMap};
+    use bevy_rapier2d::render::DebugRenderContext;
+
+    use super::*;
+    use crate::{audio::GameAudioPlugin, utils::debug::DebugPlugin};
+
+    fn app() -> App {
+        let mut gizmo_config = GizmoConfigStore::default();
+        gizmo_config.insert(GizmoConfig::default(), DefaultGizmoConfigGroup);
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .insert_resource(gizmo_config)
+            .init_resource::<DebugRenderContext>()
+            .init_resource::<GizmoStorage<DefaultGizmoConfigGroup, ()>>()
+            .add_plugins((DebugPlugin, GameAudioPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                0.5,
+            )));
+        app
+    }
+
+    #[test]
+    fn keeps_sounds_that_have_not_started_yet() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<PlayingSounds>()
+            .push(PlayingSound {
+                id: None,
+                channel: SoundChannel::Sfx,
+                bus: None,
+                volume: 1.0,
+                spacial: false,
+                handle: Handle::weak_from_u128(42),
+            });
+
+        for _ in 0..4 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<PlayingSounds>().len(), 1);
+    }
+
+    #[cfg(feature = "test-audio")]
+    #[test]
+    fn distant_sounds_start_attenuated() {
+        let mut app = app();
+        let distance = GameAudio::default().spacial_distance() as f32 / 2.0;
+        app.world_mut()
+            .spawn((GlobalTransform::IDENTITY, AudioReceiver));
+        let parent = app
+            .world_mut()
+            .spawn(GlobalTransform::from_xyz(distance, 0.0, 0.0))
+            .id();
+        app.world_mut().send_event(PlaySound {
+            parent: Some(parent),
+            ..default()
+        });
+        app.world_mut().send_event(PlaySpacialSound {
+            clip: Handle::weak_from_u128(1),
+            position: Vec2::new(0.0, distance),
+            ..default()
+        });
+        app.update();
+
+        let commands = app.world_mut().resource_mut::<AudioRecorder>().take();
+        let volumes: Vec<f64> = commands
+            .iter()
+            .filter_map(|command| match command {
+                RecordedAudioCommand::Play { volume, .. } => Some(*volume),
+                _ => None,
+            })
+            .collect();
+        let output_volume = GameAudio::default().sound_output_volume(SoundChannel::Sfx, None);
+        assert_eq!(volumes.len(), 2);
+        for volume in volumes {
+            assert!((volume - 0.5625 * output_volume).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn immediate_stop_skips_fade_out() {
+        let settings = AudioFadeSettings::default();
+        let stop = |immediate| StopSound {
+            id: SoundId(0),
+            immediate,
+        };
+
+        assert_eq!(stop(true).fade_out(&settings), Duration::ZERO);
+        assert_eq!(
+            stop(false).fade_out(&settings),
+            Duration::from_secs_f32(settings.stop_fade_out)
+        );
+    }
+
+    #[test]
+    fn audio_stats_follow_playing_sounds() {
+        let mut states = HashMap::new();
+        let mut playing_sounds = PlayingSounds::default();
+        let mut audio_stats = AudioStats::default();
+        for n in 0..3 {
+            let handle = Handle::weak_from_u128(n);
+            states.insert(handle.id(), PlaybackState::Playing { position: 0.0 });
+            playing_sounds.push(PlayingSound {
+                id: None,
+                channel: SoundChannel::Sfx,
+                bus: None,
+                volume: 1.0,
+                spacial: false,
+                handle,
+            });
+        }
+        // This one hasn't started yet.
+        playing_sounds.push(PlayingSound {
+            id: None,
+            channel: SoundChannel::Sfx,
+            bus: None,
+            volume: 1.0,
+            spacial: true,
+            handle: Handle::weak_from_u128(3),
+        });
+        fn state(
+            states: &HashMap<AssetId<AudioInstance>, PlaybackState>,
+        ) -> impl Fn(&Handle<AudioInstance>) -> Option<PlaybackState> + '_ {
+            |handle| states.get(&handle.id()).copied()
+        }
+
+        audio_stats.set_playing(playing_sounds.count_playing(state(&states)));
+        assert_eq!(audio_stats.playing, 3);
+        assert_eq!(audio_stats.peak_playing, 3);
+
+        let id = |n| Handle::<AudioInstance>::weak_from_u128(n).id();
+        states.insert(id(0), PlaybackState::Stopped);
+        states.insert(id(1), PlaybackState::Paused { position: 1.0 });
+        playing_sounds.remove_stopped(state(&states));
+        assert_eq!(playing_sounds.len(), 3);
+
+        audio_stats.set_playing(playing_sounds.count_playing(state(&states)));
+        assert_eq!(audio_stats.playing, 1);
+        assert_eq!(audio_stats.peak_playing, 3);
+    }
+}
@@ -1,7 +1,27 @@
-use bevy::prelude::*;
+use bevy::{
+    ecs::query::QuerySingleError,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 use bevy_kira_audio::prelude::*;
 
-use super::GameAudio;
+use crate::{
+    camera::MainCamera,
+    utils::debug::{CycleSoloSpacialEmitterEvent, DebugFlags, DebugState},
+};
+
+use super::{AudioBus, AudioSystem, GameAudio, SoundChannel};
+#[cfg(feature = "test-audio")]
+use super::{AudioRecorder, RecordedAudioCommand};
+
+/// How often the spacial debug info gets logged, in seconds.
+const DEBUG_LOG_INTERVAL: f32 = 1.0;
+const DEBUG_EMITTER_RADIUS: f32 = 4.0;
+const DEBUG_HEARING_RANGE_COLOR: Color = Color::srgba(0.2, 0.6, 1.0, 0.5);
+/// The speed of sound for the Doppler effect, in world units per second.
+const DOPPLER_SPEED_OF_SOUND: f32 = 1000.0;
+/// The maximum factor by which the Doppler effect changes the playback rate, in both directions.
+const MAX_DOPPLER_SHIFT: f64 = 2.0;
 
 /// Add this to any entity you want to have spacial audio on.
 /// This will adjust the volume of the corresponding audio clip
@@ -11,49 +31,484 @@ use super::GameAudio;
 /// This requires the entity to have a `Transform`
 /// and there to be exactly one `AudioReceiver`
 /// (probably on the main camera).
+///
+/// The instances that should be affected must be stored in an `AudioEmitter`
+/// on the same entity. If the entity doesn't have one yet, an empty
+/// `AudioEmitter` will be inserted automatically when this component is added.
+/// Every instance must only be stored in a single `AudioEmitter`,
+/// otherwise the emitters will fight over its volume
+/// (this is checked in debug builds).
 #[derive(Component)]
 pub struct SpacialSound {
     volume: f64,
+    min_volume: f64,
+    channel: SoundChannel,
+    bus: Option<AudioBus>,
+    screen_space: bool,
+    playback_rate: f64,
 }
 
 impl SpacialSound {
     /// Create new `SpacialSound` with given volume.
     /// This volume will be multiplied by the distance to the `AudioReceiver`.
     pub fn new(volume: f64) -> Self {
-        Self { volume }
+        Self {
+            volume,
+            min_volume: 0.0,
+            channel: SoundChannel::default(),
+            bus: None,
+            screen_space: false,
+            playback_rate: 1.0,
+        }
+    }
+
+    /// A builder to set all options of a `SpacialSound` by name,
+    /// see `SpacialSoundBuilder` for the defaults.
+    pub fn builder() -> SpacialSoundBuilder {
+        SpacialSoundBuilder::default()
+    }
+
+    /// The playback rate of the instances of this emitter, `1.0` by default.
+    /// This is only used by the Doppler effect (see `GameAudio::doppler_intensity`),
+    /// which overwrites the playback rate of the instances.
+    /// Set this to the playback rate you played the sound with,
+    /// `PlaySound` does this automatically.
+    pub fn with_playback_rate(mut self, playback_rate: f64) -> Self {
+        self.playback_rate = playback_rate;
+        self
+    }
+
+    /// Interpret the translation of the emitter as a position on the screen
+    /// (in logical pixels, like `Window::cursor_position`) instead of the world.
+    /// Useful for sounds of UI or minimap markers.
+    ///
+    /// The position is converted to the world with `Camera::viewport_to_world_2d`
+    /// of the `MainCamera`, so there must be exactly one, otherwise the sound is muted.
+    pub fn with_screen_space(mut self) -> Self {
+        self.screen_space = true;
+        self
+    }
+
+    /// The world position of the emitter with the given transform.
+    fn world_position(
+        &self,
+        transform: &GlobalTransform,
+        camera: Option<(&Camera, &GlobalTransform)>,
+    ) -> Option<Vec3> {
+        if !self.screen_space {
+            return Some(transform.translation());
+        }
+        let (camera, camera_transform) = camera?;
+        camera
+            .viewport_to_world_2d(camera_transform, transform.translation().truncate())
+            .map(|position| position.extend(0.0))
+    }
+
+    /// Apply the volume of the given channel to this sound,
+    /// `SoundChannel::Sfx` by default.
+    /// The final volume is `volume * channel volume * global volume`,
+    /// multiplied by the attenuation.
+    pub fn with_channel(mut self, channel: SoundChannel) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Apply the volume of the given bus to this sound, none by default.
+    /// See `AudioBus`.
+    pub fn with_bus(mut self, bus: Option<AudioBus>) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    /// The base volume of this sound, before attenuation, channel and global volume.
+    pub fn volume(&self) -> f64 {
+        self.volume
+    }
+
+    /// Never attenuate the sound below `min_volume`, not even out of range.
+    /// Sounds whose own volume is lower than `min_volume` are never raised above it.
+    /// Useful for distant looping ambience, which would otherwise
+    /// audibly pop in and out at the edge of the range.
+    ///
+    /// Note that looping sounds never stop on their own,
+    /// so a floored loop stays audible until you stop it yourself.
+    pub fn with_min_volume(mut self, min_volume: f64) -> Self {
+        self.min_volume = min_volume;
+        self
+    }
+
+    /// The volume before the global volume is applied.
+    fn attenuated_volume(&self, multiplier: f64) -> f64 {
+        (self.volume * multiplier).max(self.min_volume.min(self.volume))
     }
 }
 
+/// Builder for a `SpacialSound`, created with `SpacialSound::builder`.
+/// Every option that isn't set keeps its default, so
+/// `SpacialSound::builder().volume(0.8).channel(SoundChannel::Music).build()`
+/// is all you need for most sounds.
+pub struct SpacialSoundBuilder(SpacialSound);
+
+impl Default for SpacialSoundBuilder {
+    fn default() -> Self {
+        Self(SpacialSound::new(1.0))
+    }
+}
+
+impl SpacialSoundBuilder {
+    /// The base volume, `1.0` by default.
+    pub fn volume(mut self, volume: f64) -> Self {
+        self.0.volume = volume;
+        self
+    }
+
+    /// The volume the sound is never attenuated below, `0.0` by default.
+    /// See `SpacialSound::with_min_volume`.
+    pub fn min_volume(self, min_volume: f64) -> Self {
+        Self(self.0.with_min_volume(min_volume))
+    }
+
+    /// The channel of the sound, `SoundChannel::Sfx` by default.
+    /// See `SpacialSound::with_channel`.
+    pub fn channel(self, channel: SoundChannel) -> Self {
+        Self(self.0.with_channel(channel))
+    }
+
+    /// The bus of the sound, none by default.
+    /// See `SpacialSound::with_bus`.
+    pub fn bus(self, bus: AudioBus) -> Self {
+        Self(self.0.with_bus(Some(bus)))
+    }
+
+    /// Whether the position is on the screen instead of in the world, `false` by default.
+    /// See `SpacialSound::with_screen_space`.
+    pub fn screen_space(mut self, screen_space: bool) -> Self {
+        self.0.screen_space = screen_space;
+        self
+    }
+
+    /// The playback rate of the instances, `1.0` by default.
+    /// See `SpacialSound::with_playback_rate`.
+    pub fn playback_rate(self, playback_rate: f64) -> Self {
+        Self(self.0.with_playback_rate(playback_rate))
+    }
+
+    /// Build the `SpacialSound`.
+    pub fn build(self) -> SpacialSound {
+        self.0
+    }
+}
+
+/// How spacial sounds are panned between the left and right speaker.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpacialPanning {
+    /// No panning, spacial sounds are always centered.
+    #[default]
+    Off,
+    /// Pan based on the horizontal offset between emitter and receiver in world space.
+    /// Use this if your camera never rotates.
+    World,
+    /// Pan based on the offset between emitter and receiver in the local space
+    /// of the receiver, so that left and right are relative to its rotation.
+    /// Use this if your camera rotates.
+    Listener,
+}
+
+/// How the audible range of spacial sounds is determined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpacialRange {
+    /// `max_spacial_distance` is a fixed distance in world units.
+    #[default]
+    World,
+    /// `max_spacial_distance` is multiplied by the scale of the main camera's projection,
+    /// so that roughly the same part of the screen is audible regardless of the zoom.
+    /// Useful for games that zoom during gameplay.
+    ProjectionScaled,
+}
+
+/// Hear all spacial sounds from this world position instead of the `AudioReceiver`,
+/// for example to follow an NPC during a cutscene while the camera is elsewhere.
+/// Set it back to `None` to use the `AudioReceiver` again.
+///
+/// This applies to every spacial sound, there are no overrides per emitter.
+/// The overridden listener has no rotation, so `SpacialPanning::Listener`
+/// behaves like `SpacialPanning::World` while it is set.
+#[derive(Resource, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct SpacialListenerOverride(pub Option<Vec2>);
+
+/// The transform that spacial sounds are heard from,
+/// either the `SpacialListenerOverride` or the `AudioReceiver`.
+pub(super) fn listener_transform(
+    listener_override: &SpacialListenerOverride,
+    receiver: &Query<&GlobalTransform, With<AudioReceiver>>,
+) -> Result<GlobalTransform, QuerySingleError> {
+    match listener_override.0 {
+        Some(position) => Ok(GlobalTransform::from_translation(position.extend(0.0))),
+        None => receiver.get_single().copied(),
+    }
+}
+
+/// The spacial emitter that is soloed with `CycleSoloSpacialEmitterEvent`, if any.
+#[derive(Resource, Default)]
+pub(super) struct SoloSpacialEmitter(pub(super) Option<Entity>);
+
+/// How the distance between emitter and receiver is turned into a volume.
+/// In all cases sounds are inaudible at `max_spacial_distance`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SpacialAttenuation {
+    /// Based on the squared distance ratio, `(1 - d² / max²)²`.
+    /// Sounds stay loud for most of the range and drop off near the edge,
+    /// at half the distance the volume is `0.5625`.
+    #[default]
+    Squared,
+    /// Based on the true distance ratio, `(1 - d / max)²`.
+    /// Sounds get quieter right away, at half the distance the volume is `0.25`.
+    Linear,
+    /// The inverse distance model that most engines use.
+    /// Sounds are at full volume up to `reference_distance`, beyond that the volume is
+    /// `reference / (reference + rolloff * (d - reference))`, so at twice the reference
+    /// distance with a rolloff of `1.0` the volume is `0.5`.
+    /// The volume is cut to zero at `max_spacial_distance`.
+    ///
+    /// Both distances are scaled by the `spacial_range`.
+    InverseDistance {
+        /// The distance in world units up to which sounds play at full volume.
+        reference_distance: f64,
+        /// How fast the volume drops beyond the reference distance,
+        /// `0.0` means no drop at all.
+        rolloff_factor: f64,
+    },
+}
+
+/// The panning of a sound at `emitter` when heard from `receiver`,
+/// where `0.0` is hard left, `0.5` is centered and `1.0` is hard right.
+fn panning(game_audio: &GameAudio, emitter: Vec3, receiver: &GlobalTransform) -> Option<f64> {
+    let offset = emitter - receiver.translation();
+    let offset = match game_audio.spacial_panning {
+        SpacialPanning::Off => return None,
+        SpacialPanning::World => offset,
+        SpacialPanning::Listener => {
+            let (_, rotation, _) = receiver.to_scale_rotation_translation();
+            rotation.inverse() * offset
+        }
+    };
+    let pan = (offset.x as f64 / game_audio.spacial_distance()).clamp(-1.0, 1.0);
+    Some(0.5 + 0.5 * pan)
+}
+
+/// The factor by which the volume of a sound at `emitter`
+/// gets multiplied when heard from `receiver`.
+pub(super) fn attenuation(game_audio: &GameAudio, emitter: Vec3, receiver: Vec3) -> f64 {
+    let offset = (emitter - receiver).truncate();
+    let ratio = match game_audio.spacial_attenuation {
+        SpacialAttenuation::Squared => {
+            offset.length_squared() as f64 / game_audio.spacial_distance().powi(2)
+        }
+        SpacialAttenuation::Linear => offset.length() as f64 / game_audio.spacial_distance(),
+        SpacialAttenuation::InverseDistance {
+            reference_distance,
+            rolloff_factor,
+        } => {
+            let distance = offset.length() as f64;
+            if distance >= game_audio.spacial_distance() {
+                return 0.0;
+            }
+            let reference =
+                (reference_distance * game_audio.spacial_distance_scale).max(f64::EPSILON);
+            if distance <= reference {
+                return 1.0;
+            }
+            return reference / (reference + rolloff_factor.max(0.0) * (distance - reference));
+        }
+    };
+    (1.0 - ratio).clamp(0.0, 1.0).powi(2)
+}
+
+/// Everything besides the emitter itself that the final volume of a spacial sound depends on.
+pub(super) struct SpacialMix<'a> {
+    pub(super) game_audio: &'a GameAudio,
+    pub(super) receiver: GlobalTransform,
+    pub(super) camera: Option<(&'a Camera, &'a GlobalTransform)>,
+    pub(super) solo: Option<Entity>,
+}
+
+impl SpacialMix<'_> {
+    /// The volume and panning of the instances of the `sound` at `transform`,
+    /// before the quietest voices are culled.
+    /// The `entity` is `None` for emitters that aren't spawned yet,
+    /// those are muted while another emitter is soloed.
+    fn output(
+        &self,
+        entity: Option<Entity>,
+        transform: &GlobalTransform,
+        sound: &SpacialSound,
+    ) -> (f64, Option<f64>) {
+        if self.solo.is_some() && self.solo != entity {
+            return (0.0, None);
+        }
+        let Some(position) = sound.world_position(transform, self.camera) else {
+            return (0.0, None);
+        };
+        let multiplier = attenuation(self.game_audio, position, self.receiver.translation());
+        let volume = sound.attenuated_volume(multiplier)
+            * self
+                .game_audio
+                .sound_output_volume(sound.channel, sound.bus);
+        (volume, panning(self.game_audio, position, &self.receiver))
+    }
+
+    /// The volume and panning that a new spacial `sound` at `transform` starts with,
+    /// the same that `update_volumes` sets once its emitter is spawned.
+    /// If the new instance is culled, it starts muted instead,
+    /// `update_volumes` pauses it as soon as the instance exists.
+    pub(super) fn new_emitter_output(
+        &self,
+        transform: &GlobalTransform,
+        sound: &SpacialSound,
+        emitters: &Query<(Entity, &GlobalTransform, &AudioEmitter, &SpacialSound)>,
+    ) -> (f64, Option<f64>) {
+        let (volume, panning) = self.output(None, transform, sound);
+        let Some(max_voices) = self.game_audio.max_spacial_voices else {
+            return (volume, panning);
+        };
+        // The new emitter is sorted after existing emitters of the same volume.
+        let louder_instances: usize = emitters
+            .iter()
+            .filter(|(entity, emitter_transform, _, emitter_sound)| {
+                self.output(Some(*entity), emitter_transform, emitter_sound)
+                    .0
+                    >= volume
+            })
+            .map(|(_, _, emitter, _)| emitter.instances.len())
+            .sum();
+        if louder_instances >= max_voices {
+            (0.0, panning)
+        } else {
+            (volume, panning)
+        }
+    }
+}
+
+/// A single instance of a spacial emitter, with the volume and panning it should play at.
+struct Voice<'a> {
+    instance: &'a Handle<AudioInstance>,
+    channel: SoundChannel,
+    volume: f64,
+    panning: Option<f64>,
+}
+
+/// The spacial instances that are paused because of `GameAudio::max_spacial_voices`.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub(super) struct CulledSpacialVoices(HashSet<AssetId<AudioInstance>>);
+
+/// The quietest audible voices, beyond the `max_voices` loudest ones.
+fn quietest_voices(voices: &mut [Voice], max_voices: usize) -> HashSet<AssetId<AudioInstance>> {
+    voices.sort_by(|a, b| b.volume.total_cmp(&a.volume));
+    voices
+        .iter()
+        .filter(|voice| voice.volume > 0.0)
+        .skip(max_voices)
+        .map(|voice| voice.instance.id())
+        .collect()
+}
+
 fn update(
-    game_audio: &Res<GameAudio>,
-    receiver_transform: &GlobalTransform,
-    emitters: &Query<(&GlobalTransform, &AudioEmitter, &SpacialSound)>,
+    mix: &SpacialMix,
+    emitters: &Query<(Entity, &GlobalTransform, &AudioEmitter, &SpacialSound)>,
     audio_instances: &mut Assets<AudioInstance>,
+    culled_voices: &mut CulledSpacialVoices,
+    #[cfg(feature = "test-audio")] recorder: &mut AudioRecorder,
 ) {
-    for (emitter_transform, emitter, sound) in emitters {
-        let distance = (emitter_transform.translation() - receiver_transform.translation())
-            .truncate()
-            .length_squared();
-        let multiplier =
-            (1.0 - distance as f64 / game_audio.max_spacial_distance.powi(2)).clamp(0.0, 1.0);
-        let volume: f64 = sound.volume * multiplier.powi(2) * game_audio.global_volume;
+    #[cfg(debug_assertions)]
+    let mut seen_instances = HashSet::new();
 
+    let mut voices = Vec::new();
+    for (entity, emitter_transform, emitter, sound) in emitters {
+        let (volume, panning) = mix.output(Some(entity), emitter_transform, sound);
         for instance in emitter.instances.iter() {
-            if let Some(instance) = audio_instances.get_mut(instance) {
-                instance.set_volume(volume, AudioTween::default());
+            #[cfg(debug_assertions)]
+            assert!(
+                seen_instances.insert(instance.id()),
+                "audio instance {:?} is stored in more than one `AudioEmitter`",
+                instance.id()
+            );
+
+            voices.push(Voice {
+                instance,
+                channel: sound.channel,
+                volume,
+                panning,
+            });
+        }
+    }
+
+    let culled = match mix.game_audio.max_spacial_voices {
+        Some(max_voices) => quietest_voices(&mut voices, max_voices),
+        None => HashSet::new(),
+    };
+
+    for voice in voices {
+        #[cfg(feature = "test-audio")]
+        recorder.record(RecordedAudioCommand::SetVolume {
+            instance: voice.instance.clone(),
+            volume: voice.volume,
+        });
+        let Some(instance) = audio_instances.get_mut(voice.instance) else {
+            continue;
+        };
+        instance.set_volume(voice.volume, AudioTween::default());
+        if let Some(panning) = voice.panning {
+            instance.set_panning(panning, AudioTween::default());
+        }
+
+        // Culled voices are paused, so that they don't take up a voice of the audio backend.
+        if culled.contains(&voice.instance.id()) {
+            if matches!(instance.state(), PlaybackState::Playing { .. }) {
+                instance.pause(AudioTween::default());
             }
+        } else if culled_voices.contains(&voice.instance.id())
+            && !mix.game_audio.is_channel_paused(voice.channel)
+        {
+            instance.resume(AudioTween::default());
         }
     }
+    **culled_voices = culled;
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_volumes(
     game_audio: Res<GameAudio>,
+    listener_override: Res<SpacialListenerOverride>,
+    solo: Res<SoloSpacialEmitter>,
     receiver: Query<&GlobalTransform, With<AudioReceiver>>,
-    emitters: Query<(&GlobalTransform, &AudioEmitter, &SpacialSound)>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    emitters: Query<(Entity, &GlobalTransform, &AudioEmitter, &SpacialSound)>,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut culled_voices: ResMut<CulledSpacialVoices>,
+    #[cfg(feature = "test-audio")] mut recorder: ResMut<AudioRecorder>,
 ) {
-    match receiver.get_single() {
-        Ok(r) => update(&game_audio, r, &emitters, &mut audio_instances),
+    // Without emitters (in menus for example) we don't need a receiver either.
+    if emitters.is_empty() {
+        return;
+    }
+
+    match listener_transform(&listener_override, &receiver) {
+        Ok(receiver) => {
+            let mix = SpacialMix {
+                game_audio: &game_audio,
+                receiver,
+                camera: q_camera.get_single().ok(),
+                solo: solo.0,
+            };
+            update(
+                &mix,
+                &emitters,
+                &mut audio_instances,
+                &mut culled_voices,
+                #[cfg(feature = "test-audio")]
+                &mut recorder,
+            );
+        }
         Err(err) => error!(
             "There must be exactly one entity with an `AudioReceiver`. {}",
             err
@@ -61,7 +516,121 @@ fn update_volumes(
     };
 }
 
-fn cleanup_stopped_spacial_instances(
+/// Shift the playback rate of spacial sounds based on how fast
+/// they move towards or away from the receiver.
+///
+/// This keeps track of the distance of every emitter to the receiver from the last frame.
+/// It's cheap, but it does cost a hash map entry per emitter and frame,
+/// which is why it only runs if `GameAudio::doppler_intensity` is set.
+#[allow(clippy::too_many_arguments)]
+fn apply_doppler(
+    time: Res<Time>,
+    game_audio: Res<GameAudio>,
+    listener_override: Res<SpacialListenerOverride>,
+    receiver: Query<&GlobalTransform, With<AudioReceiver>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    emitters: Query<(Entity, &GlobalTransform, &AudioEmitter, &SpacialSound)>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut previous_distances: Local<HashMap<Entity, f32>>,
+) {
+    if game_audio.doppler_intensity == 0.0 {
+        previous_distances.clear();
+        return;
+    }
+    let Ok(receiver_transform) = listener_transform(&listener_override, &receiver) else {
+        return;
+    };
+    let delta = time.delta_seconds();
+    if delta == 0.0 {
+        return;
+    }
+
+    let mut distances = HashMap::new();
+    for (entity, emitter_transform, emitter, sound) in &emitters {
+        let Some(position) = sound.world_position(emitter_transform, q_camera.get_single().ok())
+        else {
+            continue;
+        };
+        let distance = position
+            .truncate()
+            .distance(receiver_transform.translation().truncate());
+        distances.insert(entity, distance);
+
+        let Some(previous_distance) = previous_distances.get(&entity) else {
+            continue;
+        };
+        // Positive if the emitter moves away from the receiver.
+        let radial_velocity = (distance - previous_distance) / delta;
+        let shift =
+            1.0 - game_audio.doppler_intensity * (radial_velocity / DOPPLER_SPEED_OF_SOUND) as f64;
+        let shift = shift.clamp(MAX_DOPPLER_SHIFT.recip(), MAX_DOPPLER_SHIFT);
+
+        for instance in emitter.instances.iter() {
+            if let Some(instance) = audio_instances.get_mut(instance) {
+                instance.set_playback_rate(sound.playback_rate * shift, AudioTween::default());
+            }
+        }
+    }
+    *previous_distances = distances;
+}
+
+fn cycle_solo_spacial_emitter(
+    debug_state: Res<DebugState>,
+    mut solo: ResMut<SoloSpacialEmitter>,
+    emitters: Query<Entity, (With<AudioEmitter>, With<SpacialSound>)>,
+    mut ev_cycle_solo: EventReader<CycleSoloSpacialEmitterEvent>,
+) {
+    if !**debug_state {
+        ev_cycle_solo.clear();
+        if solo.0.is_some() {
+            solo.0 = None;
+        }
+        return;
+    }
+    // Don't keep everything muted after the soloed emitter got despawned.
+    if solo.0.is_some_and(|entity| !emitters.contains(entity)) {
+        solo.0 = None;
+    }
+
+    for _ in ev_cycle_solo.read() {
+        let mut entities: Vec<Entity> = emitters.iter().collect();
+        entities.sort();
+        solo.0 = match solo.0 {
+            Some(current) => entities.into_iter().find(|entity| *entity > current),
+            None => entities.first().copied(),
+        };
+        match solo.0 {
+            Some(entity) => info!("soloing spacial emitter {:?}", entity),
+            None => info!("disabled spacial emitter solo"),
+        }
+    }
+}
+
+fn update_spacial_distance_scale(
+    mut game_audio: ResMut<GameAudio>,
+    q_projection: Query<&OrthographicProjection, With<MainCamera>>,
+) {
+    let scale = match (game_audio.spacial_range, q_projection.get_single()) {
+        (SpacialRange::ProjectionScaled, Ok(projection)) => projection.scale as f64,
+        _ => 1.0,
+    };
+    if game_audio.spacial_distance_scale != scale {
+        game_audio.spacial_distance_scale = scale;
+    }
+}
+
+fn insert_missing_audio_emitters(
+    mut commands: Commands,
+    q_sounds: Query<Entity, (Added<SpacialSound>, Without<AudioEmitter>)>,
+) {
+    for entity in &q_sounds {
+        commands
+            .entity(entity)
+            .insert(AudioEmitter { instances: vec![] });
+    }
+}
+
+pub(super) fn cleanup_stopped_spacial_instances(
     mut emitters: Query<&mut AudioEmitter>,
     instances: Res<Assets<AudioInstance>>,
 ) {
@@ -78,10 +647,489 @@ fn cleanup_stopped_spacial_instances(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn debug_spacial_emitters(
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    debug_state: Res<DebugState>,
+    debug_flags: Res<DebugFlags>,
+    game_audio: Res<GameAudio>,
+    listener_override: Res<SpacialListenerOverride>,
+    receiver: Query<&GlobalTransform, With<AudioReceiver>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    emitters: Query<(Entity, &GlobalTransform, &SpacialSound), With<AudioEmitter>>,
+    mut since_last_log: Local<f32>,
+) {
+    if !debug_flags.spacial_audio.is_active(&debug_state) {
+        return;
+    }
+    let Ok(receiver_transform) = listener_transform(&listener_override, &receiver) else {
+        return;
+    };
+
+    *since_last_log += time.delta_seconds();
+    let should_log = *since_last_log >= DEBUG_LOG_INTERVAL;
+    if should_log {
+        *since_last_log = 0.0;
+    }
+
+    for (entity, emitter_transform, sound) in &emitters {
+        let Some(position) = sound.world_position(emitter_transform, q_camera.get_single().ok())
+        else {
+            continue;
+        };
+        let multiplier = attenuation(&game_audio, position, receiver_transform.translation());
+        let pos = position.truncate();
+        let color = Color::srgb(1.0 - multiplier as f32, multiplier as f32, 0.0);
+        gizmos.circle_2d(pos, DEBUG_EMITTER_RADIUS, color);
+        gizmos.line_2d(pos, receiver_transform.translation().truncate(), color);
+
+        if should_log {
+            let distance = pos.distance(receiver_transform.translation().truncate());
+            info!(
+                "spacial emitter {}: distance {:.1}, attenuation {:.3}, volume {:.3}",
+                entity,
+                distance,
+                multiplier,
+                sound.attenuated_volume(multiplier)
+                    * game_audio.sound_output_volume(sound.channel, sound.bus)
+            );
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn debug_hearing_range(
+    mut gizmos: Gizmos,
+    debug_state: Res<DebugState>,
+    debug_flags: Res<DebugFlags>,
+    game_audio: Res<GameAudio>,
+    listener_override: Res<SpacialListenerOverride>,
+    receiver: Query<&GlobalTransform, With<AudioReceiver>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    emitters: Query<(&GlobalTransform, &SpacialSound), With<AudioEmitter>>,
+) {
+    if !debug_flags.hearing_range.is_active(&debug_state) {
+        return;
+    }
+
+    let max_distance = game_audio.spacial_distance() as f32;
+    let reference_distance = match game_audio.spacial_attenuation {
+        SpacialAttenuation::InverseDistance {
+            reference_distance, ..
+        } => Some((reference_distance * game_audio.spacial_distance_scale) as f32),
+        _ => None,
+    };
+    let mut draw_range = |position: Vec2| {
+        gizmos.circle_2d(position, max_distance, DEBUG_HEARING_RANGE_COLOR);
+        if let Some(reference_distance) = reference_distance {
+            gizmos.circle_2d(position, reference_distance, DEBUG_HEARING_RANGE_COLOR);
+        }
+    };
+
+    if let Ok(listener) = listener_transform(&listener_override, &receiver) {
+        draw_range(listener.translation().truncate());
+    }
+    for (emitter_transform, sound) in &emitters {
+        if let Some(position) = sound.world_position(emitter_transform, q_camera.get_single().ok())
+        {
+            draw_range(position.truncate());
+        }
+    }
+}
+
 pub struct SpacialAudioPlugin;
 
 impl Plugin for SpacialAudioPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (update_volumes, cleanup_stopped_spacial_instances));
+        app.init_resource::<SpacialListenerOverride>()
+            .init_resource::<SoloSpacialEmitter>()
+            .init_resource::<CulledSpacialVoices>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        insert_missing_audio_emitters,
+                        cycle_solo_spacial_emitter,
+                        update_spacial_distance_scale,
+                        update_volumes,
+                        apply_doppler,
+                    )
+                        .chain(),
+                    debug_spacial_emitters,
+                    debug_hearing_range,
+                )
+                    .in_set(AudioSystem::SpacialUpdate),
+            )
+            .add_systems(
+                Update,
+                cleanup_stopped_spacial_instances.in_set(AudioSystem::Cleanup),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::RunSystemOnce, gizmos::gizmos::GizmoStorage, hierarchy::HierarchyPlugin,
+        transform::TransformPlugin,
+    };
+    use bevy_rapier2d::render::DebugRenderContext;
+
+    use super::*;
+    use crate::{audio::GameAudioPlugin, utils::debug::DebugPlugin};
+
+    fn app() -> App {
+        let mut gizmo_config = GizmoConfigStore::default();
+        gizmo_config.insert(GizmoConfig::default(), DefaultGizmoConfigGroup);
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransformPlugin,
+            HierarchyPlugin,
+        ))
+        .insert_resource(gizmo_config)
+        .init_resource::<DebugRenderContext>()
+        .init_resource::<GizmoStorage<DefaultGizmoConfigGroup, ()>>()
+        .add_plugins((DebugPlugin, GameAudioPlugin));
+        app
+    }
+
+    /// Run a frame and return the volumes that the spacial update set, by instance.
+    #[cfg(feature = "test-audio")]
+    fn update_spacial_volumes(app: &mut App) -> HashMap<AssetId<AudioInstance>, f64> {
+        app.world_mut().resource_mut::<AudioRecorder>().take();
+        app.update();
+        app.world_mut()
+            .resource_mut::<AudioRecorder>()
+            .take()
+            .into_iter()
+            .filter_map(|command| match command {
+                RecordedAudioCommand::SetVolume { instance, volume } => {
+                    Some((instance.id(), volume))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn min_volume_floors_attenuation() {
+        let sound = SpacialSound::new(0.8).with_min_volume(0.2);
+        assert_eq!(sound.attenuated_volume(1.0), 0.8);
+        assert!((sound.attenuated_volume(0.5) - 0.4).abs() < 1e-9);
+        assert_eq!(sound.attenuated_volume(0.0), 0.2);
+    }
+
+    #[test]
+    fn min_volume_never_raises_quiet_sounds() {
+        let sound = SpacialSound::new(0.1).with_min_volume(0.2);
+        assert_eq!(sound.attenuated_volume(1.0), 0.1);
+        assert_eq!(sound.attenuated_volume(0.0), 0.1);
+    }
+
+    #[test]
+    fn squared_and_linear_attenuation_at_half_distance() {
+        let mut game_audio = GameAudio::default();
+        let half = Vec3::X * game_audio.spacial_distance() as f32 / 2.0;
+
+        assert!((attenuation(&game_audio, half, Vec3::ZERO) - 0.5625).abs() < 1e-6);
+        game_audio.spacial_attenuation = SpacialAttenuation::Linear;
+        assert!((attenuation(&game_audio, half, Vec3::ZERO) - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn inverse_distance_attenuation() {
+        let game_audio = GameAudio {
+            spacial_attenuation: SpacialAttenuation::InverseDistance {
+                reference_distance: 50.0,
+                rolloff_factor: 1.0,
+            },
+            ..default()
+        };
+        let at = |distance: f32| attenuation(&game_audio, Vec3::X * distance, Vec3::ZERO);
+
+        assert_eq!(at(25.0), 1.0);
+        assert_eq!(at(50.0), 1.0);
+        assert!((at(100.0) - 0.5).abs() < 1e-9);
+        assert!((at(150.0) - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(at(game_audio.spacial_distance() as f32), 0.0);
+    }
+
+    #[test]
+    fn new_emitters_start_at_their_updated_volume() {
+        let mut world = World::new();
+        let half = GameAudio::default().spacial_distance() as f32 / 2.0;
+        let existing = world
+            .spawn((
+                GlobalTransform::from_xyz(10.0, 0.0, 0.0),
+                AudioEmitter {
+                    instances: vec![Handle::weak_from_u128(1)],
+                },
+                SpacialSound::new(1.0),
+            ))
+            .id();
+
+        world.run_system_once(
+            move |emitters: Query<(Entity, &GlobalTransform, &AudioEmitter, &SpacialSound)>| {
+                let mut game_audio = GameAudio {
+                    spacial_panning: SpacialPanning::World,
+                    ..default()
+                };
+                let transform = GlobalTransform::from_xyz(half, 0.0, 0.0);
+                let out_of_range = GlobalTransform::from_xyz(4.0 * half, 0.0, 0.0);
+                let sound = SpacialSound::new(0.8).with_min_volume(0.3);
+                let mut mix = SpacialMix {
+                    game_audio: &game_audio,
+                    receiver: GlobalTransform::IDENTITY,
+                    camera: None,
+                    solo: None,
+                };
+
+                let output_volume = game_audio.sound_output_volume(SoundChannel::Sfx, None);
+
+                let (volume, panning) = mix.new_emitter_output(&transform, &sound, &emitters);
+                assert_eq!((volume, panning), mix.output(None, &transform, &sound));
+                assert!((volume - 0.8 * 0.5625 * output_volume).abs() < 1e-6);
+                assert_eq!(panning, Some(0.75));
+                assert_eq!(
+                    mix.new_emitter_output(&out_of_range, &sound, &emitters).0,
+                    0.3 * output_volume
+                );
+
+                mix.solo = Some(existing);
+                assert_eq!(mix.new_emitter_output(&transform, &sound, &emitters).0, 0.0);
+
+                game_audio.max_spacial_voices = Some(1);
+                let mix = SpacialMix {
+                    game_audio: &game_audio,
+                    receiver: GlobalTransform::IDENTITY,
+                    camera: None,
+                    solo: None,
+                };
+                // The existing emitter is louder and takes the only voice.
+                assert_eq!(mix.new_emitter_output(&transform, &sound, &emitters).0, 0.0);
+            },
+        );
+    }
+
+    #[test]
+    fn culls_single_instances_beyond_max_voices() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<GameAudio>()
+            .max_spacial_voices = Some(3);
+        app.world_mut()
+            .spawn((GlobalTransform::IDENTITY, AudioReceiver));
+
+        let mut spawn_emitter = |x: f32, instances: Vec<u128>| {
+            app.world_mut()
+                .spawn((
+                    GlobalTransform::from_xyz(x, 0.0, 0.0),
+                    AudioEmitter {
+                        instances: instances.into_iter().map(Handle::weak_from_u128).collect(),
+                    },
+                    SpacialSound::new(1.0),
+                ))
+                .id()
+        };
+        let loud = spawn_emitter(0.0, vec![1]);
+        spawn_emitter(50.0, vec![2, 3, 4]);
+        spawn_emitter(100.0, vec![5]);
+        // Out of range, so it doesn't take up a voice.
+        spawn_emitter(1000.0, vec![6]);
+        app.update();
+
+        let id = |n| Handle::<AudioInstance>::weak_from_u128(n).id();
+        let culled = |app: &App| app.world().resource::<CulledSpacialVoices>().0.clone();
+
+        // Five instances are audible, so only two are culled,
+        // one of the medium emitter and the quietest one.
+        let culled_instances = culled(&app);
+        assert_eq!(culled_instances.len(), 2);
+        assert!(culled_instances.contains(&id(5)));
+        assert!([2, 3, 4]
+            .into_iter()
+            .any(|n| culled_instances.contains(&id(n))));
+
+        // Once the loudest emitter is gone, the culled medium instance fits again.
+        app.world_mut().despawn(loud);
+        app.update();
+        assert_eq!(culled(&app), HashSet::from_iter([id(5)]));
+    }
+
+    #[cfg(feature = "test-audio")]
+    #[test]
+    fn emitters_of_the_same_source_keep_independent_volumes() {
+        use bevy_kira_audio::prelude::AudioSource;
+
+        use crate::audio::PlaySound;
+
+        let mut app = app();
+        app.world_mut()
+            .spawn((TransformBundle::default(), AudioReceiver));
+        let near = app
+            .world_mut()
+            .spawn(TransformBundle::from_transform(Transform::from_xyz(
+                50.0, 0.0, 0.0,
+            )))
+            .id();
+        let far = app
+            .world_mut()
+            .spawn(TransformBundle::from_transform(Transform::from_xyz(
+                0.0, 200.0, 0.0,
+            )))
+            .id();
+        // The same clip is only played once per frame, so play it on two frames.
+        for parent in [near, far] {
+            app.world_mut().send_event(PlaySound {
+                clip: Handle::<AudioSource>::weak_from_u128(3),
+                parent: Some(parent),
+                ..default()
+            });
+            app.update();
+        }
+
+        let instance = |app: &App, parent: Entity| {
+            let children = app.world().get::<Children>(parent).unwrap()[0];
+            app.world().get::<AudioEmitter>(children).unwrap().instances[0].id()
+        };
+        let (near_instance, far_instance) = (instance(&app, near), instance(&app, far));
+        assert_ne!(near_instance, far_instance);
+
+        let game_audio = GameAudio::default();
+        let expected = |distance: f32| {
+            attenuation(&game_audio, Vec3::X * distance, Vec3::ZERO)
+                * game_audio.sound_output_volume(SoundChannel::Sfx, None)
+        };
+        let volumes = update_spacial_volumes(&mut app);
+        assert_eq!(volumes.len(), 2);
+        assert!((volumes[&near_instance] - expected(50.0)).abs() < 1e-6);
+        assert!((volumes[&far_instance] - expected(200.0)).abs() < 1e-6);
+
+        // Moving one emitter doesn't change the volume of the other.
+        app.world_mut()
+            .get_mut::<Transform>(near)
+            .unwrap()
+            .translation
+            .x = 100.0;
+        // The transform is propagated after the spacial update.
+        app.update();
+        let volumes = update_spacial_volumes(&mut app);
+        assert!((volumes[&near_instance] - expected(100.0)).abs() < 1e-6);
+        assert!((volumes[&far_instance] - expected(200.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stopped_and_removed_sounds_dont_break_the_update() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<GameAudio>()
+            .max_spacial_voices = Some(1);
+        app.world_mut()
+            .spawn((TransformBundle::default(), AudioReceiver));
+        let mut spawn_emitter = |instance: u128| {
+            app.world_mut()
+                .spawn((
+                    TransformBundle::default(),
+                    AudioEmitter {
+                        instances: vec![Handle::weak_from_u128(instance)],
+                    },
+                    SpacialSound::new(1.0),
+                ))
+                .id()
+        };
+        let stopping = spawn_emitter(1);
+        let despawned = spawn_emitter(2);
+        app.update();
+
+        // The instances of the emitters don't exist (anymore), like stopped sounds.
+        app.world_mut()
+            .get_mut::<AudioEmitter>(stopping)
+            .unwrap()
+            .instances
+            .clear();
+        app.world_mut().despawn(despawned);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(app.world().resource::<CulledSpacialVoices>().is_empty());
+        assert!(app.world().get_entity(stopping).is_some());
+    }
+
+    #[test]
+    fn no_emitters_and_no_receiver_runs_without_error() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        use bevy::{
+            log::tracing_subscriber::{
+                layer::{Context, SubscriberExt},
+                Layer, Registry,
+            },
+            utils::tracing::{self, Level, Subscriber},
+        };
+
+        /// Counts the logged errors.
+        struct ErrorCounter(Arc<AtomicUsize>);
+
+        impl<S: Subscriber> Layer<S> for ErrorCounter {
+            fn on_event(&self, event: &tracing::Event<'_>, _: Context<'_, S>) {
+                if *event.metadata().level() == Level::ERROR {
+                    self.0.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        let errors = Arc::new(AtomicUsize::new(0));
+        let subscriber = Registry::default().with(ErrorCounter(errors.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let mut app = app();
+            for _ in 0..3 {
+                app.update();
+            }
+            assert_eq!(errors.load(Ordering::Relaxed), 0);
+
+            // An emitter without a receiver is an error though.
+            app.world_mut()
+                .spawn((TransformBundle::default(), SpacialSound::new(1.0)));
+            app.update();
+            assert!(errors.load(Ordering::Relaxed) > 0);
+        });
+    }
+
+    #[cfg(feature = "test-audio")]
+    #[test]
+    fn volumes_follow_the_listener_override() {
+        let mut app = app();
+        app.world_mut()
+            .spawn((TransformBundle::default(), AudioReceiver));
+        let instance = Handle::<AudioInstance>::weak_from_u128(1);
+        app.world_mut().spawn((
+            TransformBundle::from_transform(Transform::from_xyz(100.0, 0.0, 0.0)),
+            AudioEmitter {
+                instances: vec![instance.clone()],
+            },
+            SpacialSound::new(1.0),
+        ));
+        app.update();
+
+        let game_audio = GameAudio::default();
+        let expected = |distance: f32| {
+            attenuation(&game_audio, Vec3::X * distance, Vec3::ZERO)
+                * game_audio.sound_output_volume(SoundChannel::Sfx, None)
+        };
+        let volume = |app: &mut App| update_spacial_volumes(app)[&instance.id()];
+        assert!((volume(&mut app) - expected(100.0)).abs() < 1e-6);
+
+        **app.world_mut().resource_mut::<SpacialListenerOverride>() = Some(Vec2::new(100.0, 0.0));
+        assert!((volume(&mut app) - expected(0.0)).abs() < 1e-6);
+
+        **app.world_mut().resource_mut::<SpacialListenerOverride>() = None;
+        assert!((volume(&mut app) - expected(100.0)).abs() < 1e-6);
     }
 }
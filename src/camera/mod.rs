@@ -1,11 +1,27 @@
+mod projection;
+#[cfg(feature = "rumble")]
+mod rumble;
 mod shake;
+mod transition;
 
-pub use shake::{CameraShake, CameraSystem};
+pub use projection::{
+    visible_rect, CameraProjection, CameraScale, CameraView, CameraZoom, SetCameraScaleEvent,
+};
+#[cfg(feature = "rumble")]
+pub use rumble::TraumaRumble;
+pub use shake::{
+    fit_camera_bound, run_camera_in_state, AbsentCameraTrauma, CameraMovedEvent,
+    CameraPanFinishedEvent, CameraShake, CameraSystem, CameraTarget, ExcludeFromCameraBounds,
+    FinalCameraTransform, ShakeConfig, ShakeEndedEvent, TraumaDecayCurve,
+};
+pub use transition::{
+    SceneSwapPoint, ScreenTransitionSettings, StartScreenTransitionEvent, TransitionComplete,
+};
 
 use bevy::prelude::*;
-use bevy::render::camera::ScalingMode;
 #[cfg(not(target_arch = "wasm32"))]
 use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::render::{camera::ScalingMode, view::RenderLayers};
 #[cfg(not(target_arch = "wasm32"))]
 use bevy::window::{PrimaryWindow, WindowMode};
 use bevy_kira_audio::prelude::AudioReceiver;
@@ -21,6 +37,9 @@ use crate::utils::debug::DebugState;
 // too large values will lead to overflow of the 1000 range
 // (in which case they won't get rendered on the camera anymore).
 const YSORT_SCALE: f32 = 0.0001;
+/// Sorted z values outside of `-MAX_YSORT_Z..MAX_YSORT_Z` are likely not rendered.
+#[cfg(debug_assertions)]
+const MAX_YSORT_Z: f32 = 1000.0;
 const PROJECTION_SCALE: f32 = 200.0;
 
 /// Marker `Component` for the main camera.
@@ -28,10 +47,49 @@ const PROJECTION_SCALE: f32 = 200.0;
 #[derive(Component)]
 pub struct MainCamera;
 
+/// Get the entity of the main camera.
+/// Returns `None` if there is no main camera or more than one.
+pub fn main_camera(q_main_camera: &Query<Entity, With<MainCamera>>) -> Option<Entity> {
+    q_main_camera.get_single().ok()
+}
+
 /// Overwrites the z value of the Entities `Transform` Component
 /// based on its y value.
 #[derive(Component)]
 pub struct YSort(pub f32);
+
+/// A `SpriteBundle` that is sorted with `YSort`.
+/// The z value of the sprite's transform gets overwritten,
+/// use the `ysort` offset to draw it in front of or behind others at the same y.
+#[derive(Bundle)]
+pub struct YSortSpriteBundle {
+    /// The sprite.
+    pub sprite: SpriteBundle,
+    /// The sort offset of the sprite.
+    pub ysort: YSort,
+}
+
+impl YSortSpriteBundle {
+    /// Create a new sorted sprite with the given sort offset.
+    pub fn new(sprite: SpriteBundle, offset: f32) -> Self {
+        Self {
+            sprite,
+            ysort: YSort(offset),
+        }
+    }
+}
+/// Multiply the z value of a `YSort` or `YSortStatic` on the same entity by this factor.
+/// A `YSortChild` or `YSortStaticChild` uses the factor on its own entity,
+/// or the one of its parent if it has none, so it keeps sorting with its parent.
+/// Use values below `1.0` to compress the z range of background layers,
+/// so they sort less sensitively than the foreground.
+///
+/// Keep in mind that the z values must stay within the range the camera renders,
+/// large factors on entities far from the origin will make them disappear
+/// (this is warned about in debug builds).
+#[derive(Component)]
+pub struct YSortScale(pub f32);
+
 /// Same as `YSort` but takes into account its parent `YSort`.
 /// You will want to use this if the parent entity has a `YSort`.
 ///
@@ -48,70 +106,333 @@ pub struct YSortStatic(pub f32);
 /// but only once (when this component is added to an entity).
 #[derive(Component)]
 pub struct YSortStaticChild(pub f32);
+/// Recompute the z value of a `YSortStatic` or `YSortStaticChild` once.
+///
+/// This gets inserted automatically when one of the static components is added,
+/// because entities that were just spawned don't have a valid `GlobalTransform` yet.
+/// This way the z value is corrected on the next frame, once the transform settled.
+/// You can also insert it yourself after moving a static entity.
+#[derive(Component)]
+pub struct YSortStaticDirty;
+
+/// Set in which the z values of all `YSort` components are applied.
+/// It runs after rapier's `PhysicsSet::Writeback`
+/// and before bevy's `TransformSystem::TransformPropagate`,
+/// so run your systems `.after(YSortSystem)` to read the sorted z values
+/// before they are propagated.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct YSortSystem;
+
+/// Settings for all `YSort` components.
+#[derive(Resource)]
+pub struct YSortSettings {
+    /// The direction in which entities are sorted to the front.
+    /// The z value is the dot product of this axis and the translation.
+    /// The default `(0, -1)` draws entities with lower y in front (top down),
+    /// use something like `(-1, -1)` for isometric-ish views.
+    pub axis: Vec2,
+}
+
+impl Default for YSortSettings {
+    fn default() -> Self {
+        Self { axis: Vec2::NEG_Y }
+    }
+}
+
+impl YSortSettings {
+    /// The global z value that a `YSort` or `YSortStatic` with the given offset
+    /// gets at the global `position`, `(offset + axis · position) * YSORT_SCALE`.
+    /// Useful to debug the sort order without running the app.
+    pub fn ysort_to_z(&self, offset: f32, position: Vec2) -> f32 {
+        (offset + self.axis.dot(position)) * YSORT_SCALE
+    }
+
+    /// The inverse of `ysort_to_z`, the sort offset that results in the global `z`
+    /// at the global `position`.
+    pub fn z_to_ysort(&self, z: f32, position: Vec2) -> f32 {
+        z / YSORT_SCALE - self.axis.dot(position)
+    }
+
+    /// The local z value that a `YSortChild` or `YSortStaticChild` with the given offset
+    /// gets at the global `position`, where `parent_z` is the global z of its parent.
+    pub fn ysort_child_to_z(&self, offset: f32, position: Vec2, parent_z: f32) -> f32 {
+        self.ysort_to_z(offset, position) - parent_z
+    }
+
+    fn z(&self, offset: f32, translation: Vec3) -> f32 {
+        self.ysort_to_z(offset, translation.truncate())
+    }
+
+    /// The z value with the `YSortScale` applied, if any.
+    fn scaled_z(&self, offset: f32, translation: Vec3, scale: Option<&YSortScale>) -> f32 {
+        let z = self.z(offset, translation) * scale.map_or(1.0, |scale| scale.0);
+        #[cfg(debug_assertions)]
+        if z.abs() >= MAX_YSORT_Z {
+            warn_once!(
+                "the sorted z value {} is outside of the rendered range, \
+                reduce the `YSortScale` or keep your entities closer to the origin",
+                z
+            );
+        }
+        z
+    }
+
+    /// The local z value of a child, with the `YSortScale` applied, if any.
+    fn scaled_child_z(
+        &self,
+        offset: f32,
+        translation: Vec3,
+        parent_z: f32,
+        scale: Option<&YSortScale>,
+    ) -> f32 {
+        self.scaled_z(offset, translation, scale) - parent_z
+    }
+}
 
 /// Send this `Event` to toggle the window fullscreen.
 #[derive(Event)]
 pub struct ToggleFullscreenEvent;
 /// Zoom the camera scale level by this amount.
+/// Only works in debug mode, see `SetCameraScaleEvent` for scripted zooms.
 #[derive(Event)]
 pub struct ZoomCameraScaleEvent(pub i32);
 
-fn apply_y_sort(mut q_transforms: Query<(&mut Transform, &GlobalTransform, &YSort)>) {
-    for (mut transform, global_transform, ysort) in &mut q_transforms {
-        transform.translation.z = (ysort.0 - global_transform.translation().y) * YSORT_SCALE;
+/// Settings for the built-in screenshot system (not available on wasm).
+/// Screenshots are saved as `./screenshot-<counter>.png` in the working directory.
+#[derive(Resource)]
+pub struct ScreenshotSettings {
+    /// Whether pressing `key` takes a screenshot.
+    /// Disable this if you have your own screenshot flow.
+    pub enabled: bool,
+    /// The key that takes a screenshot, `F12` by default.
+    pub key: KeyCode,
+}
+
+impl Default for ScreenshotSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            key: KeyCode::F12,
+        }
+    }
+}
+
+/// Settings for the main camera that is spawned on startup.
+/// Insert this resource before `Startup` to change them.
+#[derive(Resource)]
+pub struct CameraSpawnSettings {
+    /// Whether to add an `AudioReceiver` to the main camera, `true` by default.
+    /// Disable this if the listener should be somewhere else, on the player for example.
+    /// Spacial audio requires exactly one `AudioReceiver`,
+    /// so you must add it to an entity of your choice yourself.
+    pub spawn_audio_receiver: bool,
+    /// Put the `AudioReceiver` on a separate `AudioListenerAnchor` entity
+    /// that follows the camera without the shake, `false` by default.
+    /// Otherwise the shake jitters the listener, which subtly modulates spacial volumes.
+    /// Only has an effect if `spawn_audio_receiver` is enabled.
+    pub stable_audio_listener: bool,
+    /// The `RenderLayers` the main camera renders, only layer `0` by default.
+    /// Change this if you have other cameras (UI, minimap) that render
+    /// separate layers, so that their entities don't show up in the main camera.
+    pub render_layers: RenderLayers,
+}
+
+/// The entity that holds the `AudioReceiver` if
+/// `CameraSpawnSettings::stable_audio_listener` is enabled.
+/// It follows the main camera, but isn't affected by the shake.
+/// This isn't a child of the camera, because then it would inherit the shake.
+#[derive(Component)]
+pub struct AudioListenerAnchor;
+
+impl Default for CameraSpawnSettings {
+    fn default() -> Self {
+        Self {
+            spawn_audio_receiver: true,
+            stable_audio_listener: false,
+            render_layers: RenderLayers::default(),
+        }
+    }
+}
+
+fn apply_y_sort(
+    settings: Res<YSortSettings>,
+    mut q_transforms: Query<(
+        &mut Transform,
+        &GlobalTransform,
+        &YSort,
+        Option<&YSortScale>,
+    )>,
+) {
+    for (mut transform, global_transform, ysort, scale) in &mut q_transforms {
+        transform.translation.z = settings.scaled_z(ysort.0, global_transform.translation(), scale);
+    }
+}
+
+/// The world z of a sorted parent.
+///
+/// We can't use the `GlobalTransform` of the parent,
+/// because its z was just changed and is only propagated later this frame.
+/// The z of the grandparent isn't changed by the sorting though,
+/// so we use its `GlobalTransform`, which also works if the parent is nested.
+fn parent_world_z(
+    parent_transform: &Transform,
+    grandparent: Option<&Parent>,
+    q_grandparents: &Query<&GlobalTransform>,
+) -> f32 {
+    match grandparent.and_then(|grandparent| q_grandparents.get(grandparent.get()).ok()) {
+        Some(grandparent_transform) => {
+            grandparent_transform
+                .transform_point(parent_transform.translation)
+                .z
+        }
+        None => parent_transform.translation.z,
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn apply_y_sort_child(
-    q_parents: Query<&Transform, (With<YSort>, Without<YSortChild>)>,
+    settings: Res<YSortSettings>,
+    q_parents: Query<
+        (&Transform, Option<&Parent>, Option<&YSortScale>),
+        (With<YSort>, Without<YSortChild>),
+    >,
+    q_grandparents: Query<&GlobalTransform>,
     mut q_transforms: Query<
-        (&Parent, &mut Transform, &GlobalTransform, &YSortChild),
+        (
+            &Parent,
+            &mut Transform,
+            &GlobalTransform,
+            &YSortChild,
+            Option<&YSortScale>,
+        ),
         Without<YSort>,
     >,
 ) {
-    for (parent, mut transform, global_transform, ysort) in &mut q_transforms {
-        let parent_transform = match q_parents.get(parent.get()) {
+    for (parent, mut transform, global_transform, ysort, scale) in &mut q_transforms {
+        let (parent_transform, grandparent, parent_scale) = match q_parents.get(parent.get()) {
             Ok(r) => r,
             Err(_) => continue,
         };
-        transform.translation.z = (ysort.0 - global_transform.translation().y) * YSORT_SCALE
-            - parent_transform.translation.z;
+        transform.translation.z = settings.scaled_child_z(
+            ysort.0,
+            global_transform.translation(),
+            parent_world_z(parent_transform, grandparent, &q_grandparents),
+            scale.or(parent_scale),
+        );
     }
 }
 
+/// Mark the entity as dirty if its static sort was just added,
+/// otherwise it was dirty and got resorted, so remove the marker.
+fn update_y_sort_static_dirty(commands: &mut Commands, entity: Entity, dirty: bool) {
+    if dirty {
+        commands.entity(entity).remove::<YSortStaticDirty>();
+    } else {
+        commands.entity(entity).insert(YSortStaticDirty);
+    }
+}
+
+#[allow(clippy::type_complexity)]
 fn apply_y_sort_static(
-    mut q_transforms: Query<(&mut Transform, &GlobalTransform, &YSortStatic), Added<YSortStatic>>,
+    mut commands: Commands,
+    settings: Res<YSortSettings>,
+    mut q_transforms: Query<
+        (
+            Entity,
+            &mut Transform,
+            &GlobalTransform,
+            &YSortStatic,
+            Option<&YSortScale>,
+            Has<YSortStaticDirty>,
+        ),
+        Or<(Added<YSortStatic>, With<YSortStaticDirty>)>,
+    >,
 ) {
-    for (mut transform, global_transform, ysort) in &mut q_transforms {
-        transform.translation.z = (ysort.0 - global_transform.translation().y) * YSORT_SCALE;
+    for (entity, mut transform, global_transform, ysort, scale, dirty) in &mut q_transforms {
+        transform.translation.z = settings.scaled_z(ysort.0, global_transform.translation(), scale);
+        update_y_sort_static_dirty(&mut commands, entity, dirty);
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn apply_y_sort_static_child(
-    q_parents: Query<&Transform, (With<YSortStatic>, Without<YSortStaticChild>)>,
+    mut commands: Commands,
+    settings: Res<YSortSettings>,
+    q_parents: Query<
+        (&Transform, Option<&Parent>, Option<&YSortScale>),
+        (With<YSortStatic>, Without<YSortStaticChild>),
+    >,
+    q_grandparents: Query<&GlobalTransform>,
     mut q_transforms: Query<
-        (&Parent, &mut Transform, &GlobalTransform, &YSortStaticChild),
-        (Added<YSortStaticChild>, Without<YSortStatic>),
+        (
+            Entity,
+            &Parent,
+            &mut Transform,
+            &GlobalTransform,
+            &YSortStaticChild,
+            Option<&YSortScale>,
+            Has<YSortStaticDirty>,
+        ),
+        (
+            Or<(Added<YSortStaticChild>, With<YSortStaticDirty>)>,
+            Without<YSortStatic>,
+        ),
     >,
 ) {
-    for (parent, mut transform, global_transform, ysort) in &mut q_transforms {
-        let parent_transform = match q_parents.get(parent.get()) {
+    for (entity, parent, mut transform, global_transform, ysort, scale, dirty) in &mut q_transforms
+    {
+        let (parent_transform, grandparent, parent_scale) = match q_parents.get(parent.get()) {
             Ok(r) => r,
             Err(_) => continue,
         };
-        transform.translation.z = (ysort.0 - global_transform.translation().y) * YSORT_SCALE
-            - parent_transform.translation.z;
+        transform.translation.z = settings.scaled_child_z(
+            ysort.0,
+            global_transform.translation(),
+            parent_world_z(parent_transform, grandparent, &q_grandparents),
+            scale.or(parent_scale),
+        );
+        update_y_sort_static_dirty(&mut commands, entity, dirty);
     }
 }
 
-fn spawn_camera(mut commands: Commands) {
+fn spawn_camera(mut commands: Commands, settings: Res<CameraSpawnSettings>) {
     let mut camera = Camera2dBundle::default();
     camera.projection.scaling_mode = ScalingMode::FixedVertical(PROJECTION_SCALE);
-    commands.spawn((MainCamera, camera, AudioReceiver));
+    let mut camera = commands.spawn((MainCamera, camera, settings.render_layers.clone()));
+    if !settings.spawn_audio_receiver {
+        return;
+    }
+    if settings.stable_audio_listener {
+        commands.spawn((
+            AudioListenerAnchor,
+            TransformBundle::default(),
+            AudioReceiver,
+        ));
+    } else {
+        camera.insert(AudioReceiver);
+    }
+}
+
+fn warn_multiple_main_cameras(
+    q_cameras: Query<Entity, With<MainCamera>>,
+    q_added_cameras: Query<(), Added<MainCamera>>,
+) {
+    if q_added_cameras.is_empty() {
+        return;
+    }
+
+    let cameras: Vec<Entity> = q_cameras.iter().collect();
+    if cameras.len() > 1 {
+        warn!(
+            "there should only be one entity with `MainCamera`, found {:?}",
+            cameras
+        );
+    }
 }
 
 fn zoom_camera(
     debug_active: Res<DebugState>,
+    mut zoom: ResMut<CameraZoom>,
     mut q_projection: Query<&mut OrthographicProjection, With<MainCamera>>,
     mut ev_zoom_camera_level: EventReader<ZoomCameraScaleEvent>,
 ) {
@@ -125,7 +446,8 @@ fn zoom_camera(
             Err(_) => continue,
         };
 
-        projection.scale = (projection.scale + ev.0 as f32).clamp(1.0, 10.0);
+        zoom.target = None;
+        projection.scale = zoom.clamp_scale(projection.scale + ev.0 as f32);
     }
 }
 
@@ -148,12 +470,13 @@ fn toggle_full_screen(mut main_window: Query<&mut Window, With<PrimaryWindow>>)
 
 #[cfg(not(target_arch = "wasm32"))]
 fn take_screenshot(
+    settings: Res<ScreenshotSettings>,
     keys: Res<ButtonInput<KeyCode>>,
     main_window: Query<Entity, With<PrimaryWindow>>,
     mut screenshot_manager: ResMut<ScreenshotManager>,
     mut counter: Local<u32>,
 ) {
-    if !keys.just_pressed(KeyCode::F12) {
+    if !settings.enabled || !keys.just_pressed(settings.key) {
         return;
     }
 
@@ -169,20 +492,61 @@ pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(shake::CameraShakePlugin)
-            .add_event::<ZoomCameraScaleEvent>()
-            .add_event::<ToggleFullscreenEvent>()
-            .add_systems(Startup, spawn_camera)
-            .add_systems(
-                Update,
-                (
-                    zoom_camera,
-                    #[cfg(not(target_arch = "wasm32"))]
-                    toggle_full_screen.run_if(on_event::<ToggleFullscreenEvent>()),
-                    #[cfg(not(target_arch = "wasm32"))]
-                    take_screenshot,
-                ),
+        app.add_plugins((
+            shake::CameraShakePlugin,
+            projection::CameraProjectionPlugin,
+            transition::ScreenTransitionPlugin,
+        ))
+        .add_event::<ZoomCameraScaleEvent>()
+        .add_event::<ToggleFullscreenEvent>()
+        .init_resource::<ScreenshotSettings>()
+        .init_resource::<CameraSpawnSettings>()
+        .init_resource::<YSortSettings>()
+        .add_systems(Startup, spawn_camera)
+        .add_systems(
+            Update,
+            (
+                warn_multiple_main_cameras,
+                zoom_camera,
+                #[cfg(not(target_arch = "wasm32"))]
+                toggle_full_screen.run_if(on_event::<ToggleFullscreenEvent>()),
+                #[cfg(not(target_arch = "wasm32"))]
+                take_screenshot,
+            ),
+        )
+        .configure_sets(
+            PostUpdate,
+            YSortSystem
+                .after(PhysicsSet::Writeback)
+                .before(TransformSystem::TransformPropagate),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                apply_y_sort,
+                apply_y_sort_child,
+                apply_y_sort_static,
+                apply_y_sort_static_child,
             )
+                .chain()
+                .in_set(YSortSystem),
+        );
+
+        #[cfg(feature = "rumble")]
+        app.add_plugins(rumble::TraumaRumblePlugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{hierarchy::HierarchyPlugin, transform::TransformPlugin};
+
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin))
+            .init_resource::<YSortSettings>()
             .add_systems(
                 PostUpdate,
                 (
@@ -192,8 +556,202 @@ impl Plugin for CameraPlugin {
                     apply_y_sort_static_child,
                 )
                     .chain()
-                    .after(PhysicsSet::Writeback)
                     .before(TransformSystem::TransformPropagate),
             );
+        app
+    }
+
+    fn z(app: &App, entity: Entity) -> f32 {
+        app.world().get::<Transform>(entity).unwrap().translation.z
+    }
+
+    #[test]
+    fn scales_y_sort() {
+        let mut app = app();
+        let position = Vec2::new(0.0, 100.0);
+        let entity = app
+            .world_mut()
+            .spawn((
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+                YSort(0.0),
+                YSortScale(0.5),
+            ))
+            .id();
+        app.update();
+        app.update();
+
+        let expected = YSortSettings::default().ysort_to_z(0.0, position) * 0.5;
+        assert!((z(&app, entity) - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn child_uses_parent_y_sort_scale() {
+        let mut app = app();
+        let parent = app
+            .world_mut()
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(0.0, 100.0, 0.0)),
+                YSort(0.0),
+                YSortScale(0.5),
+            ))
+            .id();
+        let child = app
+            .world_mut()
+            .spawn((TransformBundle::default(), YSortChild(0.0)))
+            .set_parent(parent)
+            .id();
+        let static_parent = app
+            .world_mut()
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(0.0, 100.0, 0.0)),
+                YSortStatic(0.0),
+                YSortScale(0.5),
+            ))
+            .id();
+        let static_child = app
+            .world_mut()
+            .spawn((TransformBundle::default(), YSortStaticChild(0.0)))
+            .set_parent(static_parent)
+            .id();
+        app.update();
+        app.update();
+
+        // Children at the same position as their parent sort exactly like it.
+        assert!(z(&app, parent) != 0.0);
+        assert!(z(&app, child).abs() < 1e-6);
+        assert!(z(&app, static_child).abs() < 1e-6);
+    }
+
+    #[test]
+    fn static_y_sort_is_corrected_once_transform_settled() {
+        let mut app = app();
+        // Spawned without a `GlobalTransform` that matches its `Transform` yet.
+        let entity = app
+            .world_mut()
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(0.0, 100.0, 0.0)),
+                YSortStatic(0.0),
+            ))
+            .id();
+        app.update();
+        assert!(app.world().get::<YSortStaticDirty>(entity).is_some());
+        app.update();
+
+        let expected = YSortSettings::default().ysort_to_z(0.0, Vec2::new(0.0, 100.0));
+        assert!((z(&app, entity) - expected).abs() < 1e-6);
+        assert!(app.world().get::<YSortStaticDirty>(entity).is_none());
+
+        // Static sorts don't follow the entity, unless it is marked dirty.
+        app.world_mut()
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation
+            .y = 0.0;
+        app.update();
+        app.update();
+        assert!((z(&app, entity) - expected).abs() < 1e-6);
+
+        app.world_mut().entity_mut(entity).insert(YSortStaticDirty);
+        app.update();
+        assert!(z(&app, entity).abs() < 1e-6);
+    }
+
+    #[test]
+    fn nested_child_sorts_in_world_space() {
+        let mut app = app();
+        let grandparent = app
+            .world_mut()
+            .spawn(TransformBundle::from_transform(Transform::from_xyz(
+                0.0, 50.0, 3.0,
+            )))
+            .id();
+        let parent = app
+            .world_mut()
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(0.0, 50.0, 0.0)),
+                YSort(0.0),
+            ))
+            .set_parent(grandparent)
+            .id();
+        let child = app
+            .world_mut()
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(0.0, -20.0, 0.0)),
+                YSortChild(0.0),
+            ))
+            .set_parent(parent)
+            .id();
+        app.update();
+        app.update();
+
+        let settings = YSortSettings::default();
+        let world_z = |entity| {
+            app.world()
+                .get::<GlobalTransform>(entity)
+                .unwrap()
+                .translation()
+                .z
+        };
+        let parent_z = settings.ysort_to_z(0.0, Vec2::new(0.0, 100.0));
+        let child_z = settings.ysort_to_z(0.0, Vec2::new(0.0, 80.0));
+        // The child gets the z of its world position, even though its parent is nested.
+        assert!((z(&app, parent) - parent_z).abs() < 1e-6);
+        assert!((world_z(child) - child_z).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sorts_along_diagonal_axis() {
+        let mut app = app();
+        app.insert_resource(YSortSettings {
+            axis: Vec2::new(-1.0, -1.0).normalize(),
+        });
+        let mut spawn = |x: f32, y: f32| {
+            app.world_mut()
+                .spawn((
+                    TransformBundle::from_transform(Transform::from_xyz(x, y, 0.0)),
+                    YSort(0.0),
+                ))
+                .id()
+        };
+        let front = spawn(0.0, 0.0);
+        let left = spawn(-10.0, 10.0);
+        let right = spawn(10.0, -10.0);
+        let back = spawn(10.0, 10.0);
+        let further_back = spawn(30.0, 0.0);
+        app.update();
+        app.update();
+
+        // Entities on a line perpendicular to the axis share their z.
+        assert!((z(&app, left) - z(&app, front)).abs() < 1e-6);
+        assert!((z(&app, right) - z(&app, front)).abs() < 1e-6);
+        assert!(z(&app, front) > z(&app, back));
+        assert!(z(&app, back) > z(&app, further_back));
+    }
+
+    #[test]
+    fn ysort_and_z_round_trip() {
+        let diagonal = YSortSettings {
+            axis: Vec2::new(-1.0, -1.0).normalize(),
+        };
+        for settings in [YSortSettings::default(), diagonal] {
+            for (offset, position) in [
+                (0.0, Vec2::ZERO),
+                (5.0, Vec2::new(0.0, 100.0)),
+                (-12.5, Vec2::new(-40.0, 250.0)),
+            ] {
+                let z = settings.ysort_to_z(offset, position);
+                assert!((settings.z_to_ysort(z, position) - offset).abs() < 1e-3);
+
+                // The local z of a child plus the world z of its parent is the world z.
+                let parent_z = 0.2;
+                let child_z = settings.ysort_child_to_z(offset, position, parent_z);
+                assert!((child_z + parent_z - z).abs() < 1e-6);
+                assert!((settings.z_to_ysort(child_z + parent_z, position) - offset).abs() < 1e-3);
+            }
+        }
+
+        // With the default axis this is `(ysort - y) * YSORT_SCALE`.
+        let z = YSortSettings::default().ysort_to_z(5.0, Vec2::new(30.0, 100.0));
+        assert!((z - (5.0 - 100.0) * YSORT_SCALE).abs() < 1e-9);
     }
 }
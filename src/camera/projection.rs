@@ -0,0 +1,388 @@
+use bevy::{
+    ecs::system::SystemParam,
+    math::bounding::Aabb2d,
+    prelude::*,
+    render::camera::{ScalingMode, Viewport},
+    window::{PrimaryWindow, WindowResized},
+};
+
+use super::{MainCamera, PROJECTION_SCALE};
+
+/// Scales closer than this to the zoom target snap to it.
+const ZOOM_SNAP_THRESHOLD: f32 = 0.001;
+
+/// Controls how the projection of the main camera is scaled to the window.
+/// The projection gets recomputed whenever this resource changes
+/// or the window is resized.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub enum CameraProjection {
+    /// Always show this many world units vertically,
+    /// the horizontal extent depends on the aspect ratio of the window.
+    /// This is the default.
+    FixedVertical(f32),
+    /// Show exactly `width` x `height` world units, scaled by the largest
+    /// integer factor that fits into the window. The rest of the window is letterboxed.
+    ///
+    /// Useful for pixel art, where one world unit is one texel,
+    /// because `FixedVertical` results in non-integer scales which cause shimmering.
+    IntegerScale {
+        /// The width of the visible area in world units.
+        width: u32,
+        /// The height of the visible area in world units.
+        height: u32,
+    },
+    /// Keep the amount of world units per (logical) pixel constant,
+    /// so that the world looks the same size regardless of the window size.
+    /// The visible height is clamped between `min_height` and `max_height`.
+    ///
+    /// Unlike `FixedVertical`, larger windows show more of the world,
+    /// which can be an advantage for players with big screens.
+    WorldUnitsPerPixel {
+        /// How many world units a single pixel spans.
+        units_per_pixel: f32,
+        /// The minimum visible height in world units.
+        min_height: f32,
+        /// The maximum visible height in world units.
+        max_height: f32,
+    },
+}
+
+impl Default for CameraProjection {
+    fn default() -> Self {
+        Self::FixedVertical(PROJECTION_SCALE)
+    }
+}
+
+/// `SystemParam` to get the part of the world that the main camera currently sees.
+#[derive(SystemParam)]
+pub struct CameraView<'w, 's> {
+    q_camera: Query<
+        'w,
+        's,
+        (&'static GlobalTransform, &'static OrthographicProjection),
+        With<MainCamera>,
+    >,
+}
+
+impl CameraView<'_, '_> {
+    /// The world space rectangle that the main camera currently sees,
+    /// see `visible_rect`.
+    /// Returns `None` if there is not exactly one main camera.
+    pub fn visible_rect(&self) -> Option<Aabb2d> {
+        let (transform, projection) = self.q_camera.get_single().ok()?;
+        Some(visible_rect(transform, projection))
+    }
+}
+
+/// The world space rectangle that is visible through a camera
+/// with the given transform and projection, taking the zoom into account.
+/// The translation includes the camera shake offset, the rotation is ignored.
+pub fn visible_rect(transform: &GlobalTransform, projection: &OrthographicProjection) -> Aabb2d {
+    Aabb2d::new(
+        transform.translation().truncate() + projection.area.center(),
+        projection.area.half_size(),
+    )
+}
+
+/// Set the scale of the main camera's projection to this value.
+/// The scale smoothly moves towards it and is clamped by the `CameraZoom` limits.
+///
+/// Unlike `ZoomCameraScaleEvent`, this works regardless of the `DebugState`,
+/// which makes it suitable for scripted zooms in cutscenes.
+/// To restore the zoom afterwards, send this event again with the previous scale.
+#[derive(Event)]
+pub struct SetCameraScaleEvent(pub f32);
+
+/// The current scale of the main camera's projection, updated every frame in `PostUpdate`.
+/// Use this to scale world-space UI (health bars, damage numbers) without querying the camera.
+///
+/// This is the zoom, so it includes the debug zoom (`ZoomCameraScaleEvent`),
+/// `SetCameraScaleEvent` and `CameraZoom::zoom_to_fit`, on top of the base size
+/// given by the `CameraProjection`. `1.0` if there is no main camera.
+#[derive(Resource, Clone, Copy, Debug, Deref, PartialEq)]
+pub struct CameraScale(f32);
+
+impl Default for CameraScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Settings for the zoom, the scale of the main camera's projection.
+///
+/// Sending a `ZoomCameraScaleEvent` (debug zoom) cancels any ongoing
+/// `SetCameraScaleEvent` transition, the player always takes precedence.
+#[derive(Resource, Debug)]
+pub struct CameraZoom {
+    /// The minimum scale.
+    pub min_scale: f32,
+    /// The maximum scale.
+    pub max_scale: f32,
+    /// How fast the scale moves towards the target of a `SetCameraScaleEvent`,
+    /// higher values are faster.
+    pub smoothing: f32,
+    pub(super) target: Option<f32>,
+    fit: Option<(Aabb2d, f32)>,
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self {
+            min_scale: 1.0,
+            max_scale: 10.0,
+            smoothing: 5.0,
+            target: None,
+            fit: None,
+        }
+    }
+}
+
+impl CameraZoom {
+    /// Clamp the given scale between `min_scale` and `max_scale`.
+    pub fn clamp_scale(&self, scale: f32) -> f32 {
+        scale.min(self.max_scale).max(self.min_scale)
+    }
+
+    /// Smoothly zoom so that the given rectangle plus `padding` on every side
+    /// fits on screen, respecting the aspect ratio. Useful for boss arenas or overview shots.
+    /// The scale is clamped by `min_scale` and `max_scale`,
+    /// so the rectangle might not fit if they are too restrictive.
+    ///
+    /// This only changes the zoom, move the camera to the center of the rectangle
+    /// yourself, for example with `CameraShake::pan_to`.
+    pub fn zoom_to_fit(&mut self, rect: Aabb2d, padding: f32) {
+        self.fit = Some((rect, padding));
+    }
+}
+
+/// The scale at which an area of `size` fits into the visible `area` of a projection.
+fn scale_to_fit(size: Vec2, projection: &OrthographicProjection) -> f32 {
+    // The visible area scales linearly with the scale of the projection.
+    let unscaled_size = projection.area.size() / projection.scale;
+    (size / unscaled_size).max_element()
+}
+
+fn set_camera_scale(
+    mut zoom: ResMut<CameraZoom>,
+    q_projection: Query<&OrthographicProjection, With<MainCamera>>,
+    mut ev_set_camera_scale: EventReader<SetCameraScaleEvent>,
+) {
+    if let Some(ev) = ev_set_camera_scale.read().last() {
+        zoom.target = Some(zoom.clamp_scale(ev.0));
+    }
+
+    let Ok(projection) = q_projection.get_single() else {
+        return;
+    };
+    // Only borrow the zoom mutably if there is a fit, so it isn't marked as changed every frame.
+    if let Some((rect, padding)) = zoom.fit {
+        zoom.fit = None;
+        let size = rect.max - rect.min + Vec2::splat(2.0 * padding);
+        zoom.target = Some(zoom.clamp_scale(scale_to_fit(size, projection)));
+    }
+}
+
+fn smooth_camera_scale(
+    time: Res<Time>,
+    mut zoom: ResMut<CameraZoom>,
+    mut q_projection: Query<&mut OrthographicProjection, With<MainCamera>>,
+) {
+    let Some(target) = zoom.target else {
+        return;
+    };
+    let Ok(mut projection) = q_projection.get_single_mut() else {
+        return;
+    };
+
+    let blend = 1.0 - (-zoom.smoothing * time.delta_seconds()).exp();
+    projection.scale = projection.scale.lerp(target, blend);
+    if (projection.scale - target).abs() < ZOOM_SNAP_THRESHOLD {
+        projection.scale = target;
+        zoom.target = None;
+    }
+}
+
+fn update_camera_scale(
+    mut camera_scale: ResMut<CameraScale>,
+    q_projection: Query<&OrthographicProjection, With<MainCamera>>,
+) {
+    if let Ok(projection) = q_projection.get_single() {
+        camera_scale.set_if_neq(CameraScale(projection.scale));
+    }
+}
+
+fn apply_camera_projection(
+    camera_projection: Res<CameraProjection>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut q_camera: Query<(&mut Camera, &mut OrthographicProjection, Ref<MainCamera>)>,
+    mut ev_window_resized: EventReader<WindowResized>,
+) {
+    let window_resized = ev_window_resized.read().count() > 0;
+    let Ok((mut camera, mut projection, main_camera)) = q_camera.get_single_mut() else {
+        return;
+    };
+    if !window_resized && !camera_projection.is_changed() && !main_camera.is_added() {
+        return;
+    }
+
+    match *camera_projection {
+        CameraProjection::FixedVertical(height) => {
+            projection.scaling_mode = ScalingMode::FixedVertical(height);
+            camera.viewport = None;
+        }
+        CameraProjection::WorldUnitsPerPixel {
+            units_per_pixel,
+            min_height,
+            max_height,
+        } => {
+            let Ok(window) = q_window.get_single() else {
+                return;
+            };
+            let height = (window.height() * units_per_pixel)
+                .min(max_height)
+                .max(min_height);
+            projection.scaling_mode = ScalingMode::FixedVertical(height);
+            camera.viewport = None;
+        }
+        CameraProjection::IntegerScale { width, height } => {
+            let Ok(window) = q_window.get_single() else {
+                return;
+            };
+            let area = UVec2::new(width.max(1), height.max(1));
+            let window_size = UVec2::new(window.physical_width(), window.physical_height());
+            if window_size.min_element() == 0 {
+                return;
+            }
+            let scale = (window_size / area).min_element().max(1);
+            let size = (area * scale).min(window_size);
+
+            projection.scaling_mode = ScalingMode::Fixed {
+                width: area.x as f32,
+                height: area.y as f32,
+            };
+            camera.viewport = Some(Viewport {
+                physical_position: (window_size - size) / 2,
+                physical_size: size,
+                ..default()
+            });
+        }
+    }
+}
+
+pub struct CameraProjectionPlugin;
+
+impl Plugin for CameraProjectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraProjection>()
+            .init_resource::<CameraZoom>()
+            .init_resource::<CameraScale>()
+            .add_event::<SetCameraScaleEvent>()
+            .add_systems(
+                Update,
+                (
+                    apply_camera_projection,
+                    (set_camera_scale, smooth_camera_scale).chain(),
+                ),
+            )
+            .add_systems(PostUpdate, update_camera_scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, time::TimeUpdateStrategy};
+
+    use super::*;
+
+    /// A projection that shows `200 * scale` x `100 * scale` world units.
+    fn projection(scale: f32) -> OrthographicProjection {
+        let mut projection = OrthographicProjection {
+            scale,
+            scaling_mode: ScalingMode::FixedVertical(100.0),
+            ..default()
+        };
+        bevy::render::camera::CameraProjection::update(&mut projection, 1600.0, 800.0);
+        projection
+    }
+
+    #[test]
+    fn visible_rect_of_scaled_projection() {
+        let mut world = World::new();
+        world.spawn((
+            GlobalTransform::from_xyz(10.0, 20.0, 0.0),
+            projection(2.0),
+            MainCamera,
+        ));
+
+        let rect = world.run_system_once(|camera_view: CameraView| camera_view.visible_rect());
+        let rect = rect.unwrap();
+        assert_eq!(rect.min, Vec2::new(-190.0, -80.0));
+        assert_eq!(rect.max, Vec2::new(210.0, 120.0));
+    }
+
+    #[test]
+    fn zoom_to_fit_targets_clamped_scale() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<CameraZoom>()
+            .add_event::<SetCameraScaleEvent>()
+            .add_systems(Update, set_camera_scale);
+        app.world_mut().spawn((projection(1.0), MainCamera));
+        let zoom_to_fit = |app: &mut App, size: Vec2| {
+            app.world_mut()
+                .resource_mut::<CameraZoom>()
+                .zoom_to_fit(Aabb2d::new(Vec2::new(30.0, -40.0), size / 2.0), 10.0);
+            app.update();
+            app.world().resource::<CameraZoom>().target
+        };
+
+        // With the padding the rect is 400 x 200, twice the visible area.
+        assert_eq!(zoom_to_fit(&mut app, Vec2::new(380.0, 180.0)), Some(2.0));
+        // The height doesn't fit at a scale of 2.0, so it decides.
+        assert_eq!(zoom_to_fit(&mut app, Vec2::new(180.0, 280.0)), Some(3.0));
+        assert_eq!(zoom_to_fit(&mut app, Vec2::new(4000.0, 0.0)), Some(10.0));
+        assert_eq!(zoom_to_fit(&mut app, Vec2::ZERO), Some(1.0));
+    }
+
+    #[test]
+    fn camera_scale_follows_zoom() {
+        /// Whether the `CameraZoom` changed since the last frame.
+        #[derive(Resource, Default)]
+        struct ZoomChanged(bool);
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<CameraZoom>()
+            .init_resource::<CameraScale>()
+            .init_resource::<ZoomChanged>()
+            .add_event::<SetCameraScaleEvent>()
+            .add_systems(Update, (set_camera_scale, smooth_camera_scale).chain())
+            .add_systems(PostUpdate, update_camera_scale)
+            .add_systems(
+                Last,
+                |zoom: Res<CameraZoom>, mut zoom_changed: ResMut<ZoomChanged>| {
+                    zoom_changed.0 = zoom.is_changed();
+                },
+            )
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                250,
+            )));
+        app.world_mut().spawn((projection(1.0), MainCamera));
+        app.update();
+        app.update();
+        assert_eq!(**app.world().resource::<CameraScale>(), 1.0);
+        assert!(!app.world().resource::<ZoomChanged>().0);
+
+        app.world_mut().send_event(SetCameraScaleEvent(3.0));
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(**app.world().resource::<CameraScale>(), 3.0);
+
+        // Once the scale arrived at the target, the zoom stays unchanged.
+        app.update();
+        assert!(!app.world().resource::<ZoomChanged>().0);
+    }
+}
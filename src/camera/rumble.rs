@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use bevy::{
+    input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest},
+    prelude::*,
+};
+
+use super::CameraShake;
+
+/// The duration of every rumble request.
+/// Requests are renewed each frame, so this only needs to outlast a frame.
+const RUMBLE_DURATION: Duration = Duration::from_millis(100);
+
+/// Drive the rumble of all connected gamepads by the trauma of the `CameraShake`.
+/// Disabled by default.
+#[derive(Resource)]
+pub struct TraumaRumble {
+    /// Whether the rumble is enabled.
+    pub enabled: bool,
+    /// The rumble intensity is the squared trauma multiplied by this value.
+    pub scale: f32,
+}
+
+impl Default for TraumaRumble {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scale: 1.0,
+        }
+    }
+}
+
+fn rumble_gamepads(
+    gamepads: Res<Gamepads>,
+    shake: Res<CameraShake>,
+    trauma_rumble: Res<TraumaRumble>,
+    mut ev_rumble: EventWriter<GamepadRumbleRequest>,
+    mut is_rumbling: Local<bool>,
+) {
+    let intensity = if trauma_rumble.enabled {
+        (shake.trauma().powi(2) * trauma_rumble.scale).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    if intensity == 0.0 && !*is_rumbling {
+        return;
+    }
+
+    for gamepad in gamepads.iter() {
+        ev_rumble.send(GamepadRumbleRequest::Stop { gamepad });
+        if intensity > 0.0 {
+            ev_rumble.send(GamepadRumbleRequest::Add {
+                duration: RUMBLE_DURATION,
+                intensity: GamepadRumbleIntensity {
+                    strong_motor: intensity,
+                    weak_motor: intensity,
+                },
+                gamepad,
+            });
+        }
+    }
+    *is_rumbling = intensity > 0.0;
+}
+
+pub struct TraumaRumblePlugin;
+
+impl Plugin for TraumaRumblePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TraumaRumble>()
+            .add_systems(Update, rumble_gamepads);
+    }
+}
@@ -1,10 +1,12 @@
 use chrono::Utc;
 
-use bevy::{prelude::*, transform::TransformSystem};
+use bevy::{math::bounding::Aabb2d, prelude::*, transform::TransformSystem, window::PrimaryWindow};
 use bevy_rapier2d::plugin::PhysicsSet;
 use noisy_bevy::simplex_noise_2d_seeded;
 
-use super::MainCamera;
+use crate::utils::{aabb_enclosing, debug::TraumaBreakdown};
+
+use super::{AudioListenerAnchor, MainCamera};
 
 /// Sets that are used to control the camera's transform.
 /// They run after rapier's `PhysicsSet::Writeback`
@@ -19,27 +21,231 @@ pub enum CameraSystem {
     TransformUpdate,
 }
 
+/// Set in which the trauma of the `CameraShake` decays.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct TraumaDecaySet;
+
+/// Trauma below this value is treated as zero when decaying exponentially.
+const MIN_EXPONENTIAL_TRAUMA: f32 = 0.001;
+
+/// The largest delta in seconds the trauma decays by in a single frame.
+/// Unfocused windows may be throttled, which would otherwise snap the trauma to zero on refocus.
+const MAX_TRAUMA_DECAY_DELTA: f32 = 0.1;
+
+/// Movements of the camera smaller than this don't send a `CameraMovedEvent`.
+const CAMERA_MOVED_EPSILON: f32 = 0.001;
+
+/// Sent whenever the main camera moved, including the movement from the shake.
+/// Useful for parallax backgrounds and minimaps.
+#[derive(Event)]
+pub struct CameraMovedEvent {
+    /// The new translation of the camera.
+    pub translation: Vec2,
+    /// The movement since the last camera update.
+    pub delta: Vec2,
+}
+
+/// The final transform of the main camera, including the shake,
+/// updated at the end of `CameraSystem::TransformUpdate`.
+/// Read this instead of the camera's `Transform`,
+/// so you don't need to schedule your system right after the camera update.
+/// Note that it is one frame behind if read before `CameraSystem::TransformUpdate`.
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct FinalCameraTransform {
+    /// The translation of the camera.
+    pub translation: Vec3,
+    /// The rotation of the camera.
+    pub rotation: Quat,
+}
+
+/// How the trauma of the `CameraShake` decays over time.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TraumaDecayCurve {
+    /// Reduce the trauma by `1.0` per second.
+    #[default]
+    Linear,
+    /// Multiply the trauma by the given factor every second.
+    /// The factor should be between `0.0..1.0`, lower values decay faster.
+    Exponential(f32),
+    /// Decay fast at first and slow down towards the end.
+    /// Like `Linear`, it takes at most one second to reach zero.
+    EaseOut,
+}
+
+/// All tunables of the shake, to set them in one call with `CameraShake::configure_shake`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShakeConfig {
+    /// See `CameraShake::set_noise_strength`.
+    pub noise_strength: f32,
+    /// See `CameraShake::set_translation_shake_strength`.
+    pub translation_shake_strength: f32,
+    /// See `CameraShake::set_rotation_shake_strength`.
+    pub rotation_shake_strength: f32,
+    /// See `CameraShake::set_decay_curve`.
+    pub decay_curve: TraumaDecayCurve,
+    /// See `CameraShake::set_trauma_exponent`.
+    pub trauma_exponent: f32,
+    /// See `CameraShake::set_noise_octaves`.
+    pub noise_octaves: u32,
+    /// See `CameraShake::set_noise_octaves`.
+    pub noise_persistence: f32,
+}
+
+impl Default for ShakeConfig {
+    fn default() -> Self {
+        Self {
+            noise_strength: 10.0,
+            translation_shake_strength: 15.0,
+            rotation_shake_strength: 2.5,
+            decay_curve: TraumaDecayCurve::default(),
+            trauma_exponent: 2.0,
+            noise_octaves: 1,
+            noise_persistence: 0.5,
+        }
+    }
+}
+
+/// A blend from one bound to another, see `CameraShake::set_bound_eased`.
+#[derive(Clone, Copy, Debug)]
+struct BoundTransition {
+    from: Aabb2d,
+    elapsed: f32,
+    duration: f32,
+    easing: fn(f32) -> f32,
+}
+
+/// What happens to the trauma while there is no `MainCamera`,
+/// for example during loading.
+/// Without this, trauma added in that time would burst once the camera spawns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AbsentCameraTrauma {
+    /// Keep the trauma, but cap it at the given value.
+    Hold(f32),
+    /// Clear the trauma.
+    Clear,
+}
+
+impl Default for AbsentCameraTrauma {
+    fn default() -> Self {
+        Self::Hold(0.3)
+    }
+}
+
+/// A scripted pan of the camera, see `CameraShake::pan_to`.
+#[derive(Clone, Copy, Debug)]
+struct CameraPan {
+    from: Vec2,
+    to: Vec2,
+    elapsed: f32,
+    duration: f32,
+    easing: fn(f32) -> f32,
+}
+
+impl CameraPan {
+    fn position(&self) -> Vec2 {
+        let t = if self.duration > 0.0 {
+            (self.easing)((self.elapsed / self.duration).clamp(0.0, 1.0))
+        } else {
+            1.0
+        };
+        self.from.lerp(self.to, t)
+    }
+}
+
+/// Sent once when the trauma returns to zero, which means the shake has ended.
+/// This is also sent if the trauma was cleared with `CameraShake::clear_trauma`.
+#[derive(Event)]
+pub struct ShakeEndedEvent;
+
+/// Sent when a pan started with `CameraShake::pan_to` has finished.
+#[derive(Event)]
+pub struct CameraPanFinishedEvent;
+
 /// Use to add trauma/shake to your camera.
 /// You must use this resource to update the camera's position.
-#[derive(Resource)]
+#[derive(Resource, Debug)]
 pub struct CameraShake {
     trauma: f32,
+    sustained_trauma: f32,
+    trauma_rise_rate: Option<f32>,
+    pending_trauma: f32,
     seed: f32,
     target: Vec2,
+    target_offset: Vec2,
+    locked: Option<Vec2>,
+    keep_trauma_while_locked: bool,
+    follow_smoothing: f32,
+    catch_up_distance: f32,
+    catch_up_multiplier: f32,
+    snap_to_new_target: bool,
+    camera_z: Option<f32>,
+    bound: Option<Aabb2d>,
+    bound_transition: Option<BoundTransition>,
+    clamped: BVec2,
+    pan: Option<CameraPan>,
     noise_strength: f32,
+    noise_fn: fn(Vec2, f32) -> f32,
+    noise_octaves: u32,
+    noise_persistence: f32,
     translation_shake_strength: f32,
     rotation_shake_strength: f32,
+    /// Temporary multipliers of the shake strengths and their remaining durations.
+    shake_scales: Vec<(f32, f32)>,
+    decay_curve: TraumaDecayCurve,
+    trauma_exponent: f32,
+    absent_camera_trauma: AbsentCameraTrauma,
+    pause_decay_when_unfocused: bool,
+    look_ahead: f32,
+    look_ahead_smoothing: f32,
+    look_ahead_direction: Vec2,
+    look_ahead_offset: Vec2,
+    freeze_timer: f32,
+    translation_offset: Vec3,
+    rotation_offset: f32,
 }
 
 impl Default for CameraShake {
     fn default() -> Self {
+        let config = ShakeConfig::default();
         Self {
             trauma: 0.0,
+            sustained_trauma: 0.0,
+            trauma_rise_rate: None,
+            pending_trauma: 0.0,
             seed: 0.0,
             target: Vec2::ZERO,
-            noise_strength: 10.0,
-            translation_shake_strength: 15.0,
-            rotation_shake_strength: 2.5,
+            target_offset: Vec2::ZERO,
+            locked: None,
+            keep_trauma_while_locked: true,
+            follow_smoothing: 0.0,
+            catch_up_distance: 0.0,
+            catch_up_multiplier: 1.0,
+            snap_to_new_target: true,
+            camera_z: None,
+            bound: None,
+            bound_transition: None,
+            clamped: BVec2::FALSE,
+            pan: None,
+            noise_strength: config.noise_strength,
+            noise_fn: simplex_noise_2d_seeded,
+            noise_octaves: config.noise_octaves,
+            noise_persistence: config.noise_persistence,
+            translation_shake_strength: config.translation_shake_strength,
+            rotation_shake_strength: config.rotation_shake_strength,
+            shake_scales: Vec::new(),
+            decay_curve: config.decay_curve,
+            trauma_exponent: config.trauma_exponent,
+            absent_camera_trauma: AbsentCameraTrauma::default(),
+            pause_decay_when_unfocused: true,
+            look_ahead: 0.0,
+            look_ahead_smoothing: 5.0,
+            look_ahead_direction: Vec2::ZERO,
+            look_ahead_offset: Vec2::ZERO,
+            freeze_timer: 0.0,
+            translation_offset: Vec3::ZERO,
+            rotation_offset: 0.0,
         }
     }
 }
@@ -47,13 +253,70 @@ impl Default for CameraShake {
 impl CameraShake {
     /// Add trauma to the camera shake.
     /// Trauma value is capped at `1.0`.
+    /// If a rise rate is set (see `set_trauma_rise_rate`),
+    /// the trauma is added gradually instead of instantly.
     pub fn add_trauma(&mut self, trauma: f32) {
+        if self.locked.is_some() && !self.keep_trauma_while_locked {
+            return;
+        }
+        match self.trauma_rise_rate {
+            Some(_) => self.pending_trauma = (self.pending_trauma + trauma.abs()).min(1.0),
+            None => self.set_trauma(self.trauma + trauma.abs()),
+        }
+    }
+
+    /// Limit how fast the trauma from `add_trauma` can rise, in trauma per second.
+    /// This way a burst of additions in a single frame builds up smoothly
+    /// instead of popping to the maximum. Note that the trauma still decays while rising.
+    /// Use `None` to add trauma instantly (the default),
+    /// which also applies any trauma that is still pending.
+    pub fn set_trauma_rise_rate(&mut self, rate: Option<f32>) {
+        self.trauma_rise_rate = rate;
+        if rate.is_none() && self.pending_trauma > 0.0 {
+            self.set_trauma(self.trauma + self.pending_trauma);
+            self.pending_trauma = 0.0;
+        }
+    }
+
+    /// The current trauma, between `0.0..1.0`.
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Set the trauma to the exact given value.
+    /// Trauma value is clamped between `0.0..1.0`.
+    /// Useful for scripted sequences that need full control over the shake.
+    pub fn set_trauma(&mut self, trauma: f32) {
         if self.trauma == 0.0 {
             // Get the milliseconds only.
             // We do this to get a pseudo random seed.
             self.seed = (Utc::now().timestamp_millis() & 0xFFFF) as f32;
         }
-        self.trauma = (self.trauma + trauma.abs()).min(1.0);
+        self.trauma = trauma.clamp(0.0, 1.0);
+    }
+
+    /// Immediately stop any camera shake by setting the trauma to `0.0`.
+    /// This also releases any sustained trauma.
+    pub fn clear_trauma(&mut self) {
+        self.trauma = 0.0;
+        self.pending_trauma = 0.0;
+        self.sustained_trauma = 0.0;
+    }
+
+    /// Sustain the trauma at the given level until `release_sustained_trauma` is called.
+    /// Trauma added on top of this still decays, but never below this level.
+    /// Useful for continuous effects like an earthquake.
+    /// The level is clamped between `0.0..1.0`.
+    pub fn set_sustained_trauma(&mut self, level: f32) {
+        self.sustained_trauma = level.clamp(0.0, 1.0);
+        if self.trauma < self.sustained_trauma {
+            self.set_trauma(self.sustained_trauma);
+        }
+    }
+
+    /// Release the sustained trauma, the trauma will decay normally again.
+    pub fn release_sustained_trauma(&mut self) {
+        self.sustained_trauma = 0.0;
     }
 
     /// Add trauma with an additional local threshold.
@@ -61,23 +324,105 @@ impl CameraShake {
     /// Useful if you want to make sure that incremental
     /// trauma additions don't escalate.
     pub fn add_trauma_with_threshold(&mut self, trauma: f32, threshold: f32) {
-        if self.trauma >= threshold {
+        if self.trauma + self.pending_trauma >= threshold {
             return;
         }
         self.add_trauma(trauma);
     }
 
+    /// Hold the current shake offset for `duration` seconds, then resume shaking.
+    /// The trauma doesn't decay while the shake is frozen.
+    /// Useful for hit-stops, the camera stays displaced instead of jittering
+    /// or snapping back to the target.
+    /// Calling this while already frozen only extends the freeze.
+    pub fn freeze_shake(&mut self, duration: f32) {
+        self.freeze_timer = self.freeze_timer.max(duration);
+    }
+
+    /// Whether the shake is currently frozen, see `freeze_shake`.
+    pub fn is_shake_frozen(&self) -> bool {
+        self.freeze_timer > 0.0
+    }
+
+    /// Set all shake tunables at once.
+    pub fn configure_shake(&mut self, config: ShakeConfig) {
+        self.noise_strength = config.noise_strength;
+        self.translation_shake_strength = config.translation_shake_strength;
+        self.rotation_shake_strength = config.rotation_shake_strength;
+        self.decay_curve = config.decay_curve;
+        self.trauma_exponent = config.trauma_exponent;
+        self.noise_octaves = config.noise_octaves;
+        self.noise_persistence = config.noise_persistence;
+    }
+
+    /// The current shake tunables, see `configure_shake`.
+    pub fn shake_config(&self) -> ShakeConfig {
+        ShakeConfig {
+            noise_strength: self.noise_strength,
+            translation_shake_strength: self.translation_shake_strength,
+            rotation_shake_strength: self.rotation_shake_strength,
+            decay_curve: self.decay_curve,
+            trauma_exponent: self.trauma_exponent,
+            noise_octaves: self.noise_octaves,
+            noise_persistence: self.noise_persistence,
+        }
+    }
+
+    /// Multiply the translation and rotation shake strengths by `scale`
+    /// for `duration` seconds, after which they are restored.
+    /// Useful for big scripted moments like boss phases.
+    ///
+    /// Overlapping pushes stack multiplicatively, so pushing `2.0` twice
+    /// quadruples the strengths until the first one expires.
+    pub fn push_shake_scale(&mut self, scale: f32, duration: f32) {
+        self.shake_scales.push((scale, duration));
+    }
+
+    /// The product of all active scales of `push_shake_scale`.
+    fn shake_scale(&self) -> f32 {
+        self.shake_scales.iter().map(|(scale, _)| scale).product()
+    }
+
+    fn tick_shake_scales(&mut self, delta: f32) {
+        for (_, remaining) in &mut self.shake_scales {
+            *remaining -= delta;
+        }
+        self.shake_scales.retain(|(_, remaining)| *remaining > 0.0);
+    }
+
     /// Update the `noise_strength` value.
     pub fn set_noise_strength(&mut self, noise_strength: f32) {
         self.noise_strength = noise_strength;
     }
 
+    /// Replace the noise function that drives the shake,
+    /// which is `noisy_bevy::simplex_noise_2d_seeded` by default.
+    /// The function gets a position and a seed and should return values
+    /// roughly between `-1.0..1.0`. Use this to change the character of the shake,
+    /// for example with perlin or value noise.
+    pub fn set_noise_fn(&mut self, noise_fn: fn(Vec2, f32) -> f32) {
+        self.noise_fn = noise_fn;
+    }
+
+    /// Layer `octaves` of the noise on top of each other for a more organic shake.
+    /// Every octave has twice the frequency of the previous one and its amplitude
+    /// multiplied by `persistence`, so lower values make the finer octaves subtler.
+    /// Defaults to a single octave, with a persistence of `0.5`.
+    pub fn set_noise_octaves(&mut self, octaves: u32, persistence: f32) {
+        self.noise_octaves = octaves.max(1);
+        self.noise_persistence = persistence;
+    }
+
     /// Update the `translation_shake_strength` value.
     pub fn set_translation_shake_strength(&mut self, translation_shake_strength: f32) {
         self.translation_shake_strength = translation_shake_strength;
     }
 
-    /// Update the `rotation_shake_strength` value.
+    /// Update the `rotation_shake_strength` value, the maximum rotation in degrees.
+    /// The rotation is around the z axis, so positive noise values rotate
+    /// the camera counterclockwise (bevy's right-handed convention).
+    /// Use a negative strength to flip the direction,
+    /// for example if your camera is mirrored.
     pub fn set_rotation_shake_strength(&mut self, rotation_shake_strength: f32) {
         self.rotation_shake_strength = rotation_shake_strength;
     }
@@ -91,37 +436,509 @@ impl CameraShake {
         self.target = target;
     }
 
+    /// Hold the camera at `position`, ignoring the target, pans, the bound and the shake
+    /// until `unlock` is called. Useful for scripted moments or fixed-camera rooms.
+    ///
+    /// Trauma still accumulates and decays while locked and shakes the camera after unlocking,
+    /// see `set_keep_trauma_while_locked`.
+    pub fn lock(&mut self, position: Vec2) {
+        self.locked = Some(position);
+        if !self.keep_trauma_while_locked {
+            self.clear_trauma();
+        }
+    }
+
+    /// Release the camera from `lock`, it follows its target again.
+    pub fn unlock(&mut self) {
+        self.locked = None;
+    }
+
+    /// Whether the camera is held in place by `lock`.
+    pub fn is_locked(&self) -> bool {
+        self.locked.is_some()
+    }
+
+    /// Whether trauma that is added while the camera is locked is kept,
+    /// `true` by default. If `false`, locking clears the trauma and `add_trauma` is ignored
+    /// while locked, so the camera doesn't start shaking when it is unlocked.
+    pub fn set_keep_trauma_while_locked(&mut self, keep: bool) {
+        self.keep_trauma_while_locked = keep;
+    }
+
+    /// Offset the camera from its target by this many world units, applied before the bound clamps it.
+    /// For example `Vec2::new(0.0, 30.0)` moves the camera up, so the target sits
+    /// below the center of the screen, showing more of what's ahead of it.
+    /// This also applies during `pan_to`, which pans the target.
+    pub fn set_target_offset(&mut self, offset: Vec2) {
+        self.target_offset = offset;
+    }
+
+    /// How smoothly the camera follows the `CameraTarget`, higher values are faster.
+    /// Use `0.0` to follow it exactly (the default).
+    pub fn set_follow_smoothing(&mut self, smoothing: f32) {
+        self.follow_smoothing = smoothing;
+    }
+
+    /// Speed up the smoothed follow (see `set_follow_smoothing`) when the camera lags
+    /// more than `distance` world units behind the `CameraTarget`, for example during a dash.
+    /// The follow speed ramps up to `multiplier` times the smoothing at twice the distance,
+    /// and eases back to the normal smoothing as the camera catches up.
+    /// Use a `multiplier` of `1.0` to disable it (the default).
+    ///
+    /// The distance is measured before the target offset, look-ahead and bound are applied.
+    pub fn set_follow_catch_up(&mut self, distance: f32, multiplier: f32) {
+        self.catch_up_distance = distance;
+        self.catch_up_multiplier = multiplier;
+    }
+
+    /// Whether the camera jumps to a `CameraTarget` on the frame it is added,
+    /// instead of smoothly sliding there from wherever it was, `true` by default.
+    pub fn set_snap_to_new_target(&mut self, snap: bool) {
+        self.snap_to_new_target = snap;
+    }
+
+    /// Set the z of the camera's translation, or `None` to keep it as is (the default).
+    /// Use this instead of changing the camera's `Transform` yourself,
+    /// which would race the camera update.
+    ///
+    /// The default 2D camera sees everything within `1000.0` of its z,
+    /// keep that in mind when combining this with `YSort`,
+    /// whose z values are tiny (`y * 0.0001`) and close to `0.0`.
+    pub fn set_camera_z(&mut self, camera_z: Option<f32>) {
+        self.camera_z = camera_z;
+    }
+
+    /// Pan the camera from its current target to `target` over `duration` seconds
+    /// using the given `easing` (see `ease_in_out`), for example for a level introduction.
+    /// `CameraPanFinishedEvent` is sent once the pan has finished.
+    ///
+    /// During the pan, `update_target` is ignored, but the target offset and look-ahead
+    /// are still applied on top, so the camera doesn't jump when the pan starts or ends.
+    /// The camera is still clamped by the bound.
+    /// Afterwards the target is `target`, until you update it again.
+    pub fn pan_to(&mut self, target: Vec2, duration: f32, easing: fn(f32) -> f32) {
+        self.pan = Some(CameraPan {
+            from: self.target,
+            to: target,
+            elapsed: 0.0,
+            duration,
+            easing,
+        });
+    }
+
+    /// Whether the camera is currently panning, see `pan_to`.
+    pub fn is_panning(&self) -> bool {
+        self.pan.is_some()
+    }
+
+    /// Lead the camera target by `distance` in the direction of movement
+    /// given by `update_look_ahead_direction`.
+    /// The offset eases in and out, higher `smoothing` values make it faster.
+    /// Use a `distance` of `0.0` to disable the look-ahead (the default).
+    pub fn set_look_ahead(&mut self, distance: f32, smoothing: f32) {
+        self.look_ahead = distance;
+        self.look_ahead_smoothing = smoothing;
+    }
+
+    /// Update the direction in which the camera should look ahead,
+    /// for example the velocity of the player.
+    /// Only the direction matters, use `Vec2::ZERO` when standing still.
+    pub fn update_look_ahead_direction(&mut self, direction: Vec2) {
+        self.look_ahead_direction = direction;
+    }
+
+    /// Restrict the camera to the given bound.
+    /// The camera will be clamped so that its visible area stays inside the bound.
+    /// If the visible area is larger than the bound, the camera is centered on it.
+    /// Use `None` to remove the bound.
+    /// This cancels any transition started by `set_bound_eased`.
+    pub fn set_bound(&mut self, bound: Option<Aabb2d>) {
+        self.bound = bound;
+        self.bound_transition = None;
+    }
+
+    /// Like `set_bound`, but blend from the current bound to the new one
+    /// over `duration` seconds using the given `easing` (see `ease_in_out`),
+    /// instead of popping. Useful when walking through a door into another room.
+    ///
+    /// If this is called during a transition (the player walks back for example),
+    /// the new transition starts from the current blended bound, so it never pops.
+    /// Transitions from or to `None` are applied immediately,
+    /// since there is nothing to blend between.
+    pub fn set_bound_eased(
+        &mut self,
+        bound: Option<Aabb2d>,
+        duration: f32,
+        easing: fn(f32) -> f32,
+    ) {
+        let (Some(from), Some(_)) = (self.effective_bound(), bound) else {
+            self.set_bound(bound);
+            return;
+        };
+        self.bound = bound;
+        self.bound_transition = Some(BoundTransition {
+            from,
+            elapsed: 0.0,
+            duration,
+            easing,
+        });
+    }
+
+    /// The current bound of the camera, if any.
+    /// During a transition this is the bound that is being transitioned to.
+    pub fn bound(&self) -> Option<Aabb2d> {
+        self.bound
+    }
+
+    /// The bound that the camera is actually clamped by,
+    /// which differs from `bound` during a transition.
+    pub fn effective_bound(&self) -> Option<Aabb2d> {
+        let bound = self.bound?;
+        let Some(transition) = self.bound_transition else {
+            return Some(bound);
+        };
+        let t = if transition.duration > 0.0 {
+            (transition.easing)((transition.elapsed / transition.duration).clamp(0.0, 1.0))
+        } else {
+            1.0
+        };
+        Some(Aabb2d {
+            min: transition.from.min.lerp(bound.min, t),
+            max: transition.from.max.lerp(bound.max, t),
+        })
+    }
+
+    /// Whether the camera was clamped by the bound on the x and y axis
+    /// in the last camera update.
+    /// Useful for "edge of the map" hints for example.
+    pub fn clamped(&self) -> BVec2 {
+        self.clamped
+    }
+
+    fn tick_bound_transition(&mut self, delta: f32) {
+        if let Some(transition) = &mut self.bound_transition {
+            transition.elapsed += delta;
+            if transition.elapsed >= transition.duration {
+                self.bound_transition = None;
+            }
+        }
+    }
+
+    /// Reset everything to the defaults.
+    /// This clears the trauma and bound, restores the default shake tuning
+    /// and moves the target back to the origin.
+    /// Useful when transitioning between very different scenes.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Update the `decay_curve` value.
+    pub fn set_decay_curve(&mut self, decay_curve: TraumaDecayCurve) {
+        self.decay_curve = decay_curve;
+    }
+
+    fn rise_trauma(&mut self, delta: f32) {
+        let Some(rate) = self.trauma_rise_rate else {
+            return;
+        };
+        if self.pending_trauma <= 0.0 {
+            return;
+        }
+        let step = (rate.abs() * delta).min(self.pending_trauma);
+        self.pending_trauma -= step;
+        self.set_trauma(self.trauma + step);
+    }
+
+    /// Whether the trauma stops decaying while the primary window is unfocused,
+    /// `true` by default. The shake then continues where it left off when alt-tabbing back.
+    /// Independent of this, the decay per frame is clamped, so a long frame
+    /// after refocusing doesn't cut the shake off.
+    pub fn set_pause_decay_when_unfocused(&mut self, pause: bool) {
+        self.pause_decay_when_unfocused = pause;
+    }
+
+    /// Set the exponent that maps the trauma to the shake amplitude,
+    /// the amplitude is `trauma^exponent`. The default is `2.0`.
+    /// Lower values make low trauma more noticeable (`1.0` is linear),
+    /// higher values make it more subtle.
+    pub fn set_trauma_exponent(&mut self, exponent: f32) {
+        self.trauma_exponent = exponent;
+    }
+
+    /// Set what happens to the trauma while there is no `MainCamera`,
+    /// `AbsentCameraTrauma::Hold(0.3)` by default.
+    pub fn set_absent_camera_trauma(&mut self, absent_camera_trauma: AbsentCameraTrauma) {
+        self.absent_camera_trauma = absent_camera_trauma;
+    }
+
+    /// What the current trauma is made of, for debugging.
+    pub fn trauma_breakdown(&self) -> TraumaBreakdown {
+        TraumaBreakdown {
+            trauma: self.trauma,
+            sustained_trauma: self.sustained_trauma,
+            pending_trauma: self.pending_trauma,
+            shake_scale: self.shake_scale(),
+            frozen: self.is_shake_frozen(),
+        }
+    }
+
     fn reduce_trauma(&mut self, delta: f32) {
-        self.trauma = (self.trauma - delta.abs()).max(0.0)
+        let delta = delta.abs();
+        let trauma = match self.decay_curve {
+            TraumaDecayCurve::Linear => self.trauma - delta,
+            TraumaDecayCurve::Exponential(factor) => {
+                let trauma = self.trauma * factor.clamp(0.0, 1.0).powf(delta);
+                if trauma < MIN_EXPONENTIAL_TRAUMA {
+                    0.0
+                } else {
+                    trauma
+                }
+            }
+            // The square root of the trauma decays linearly.
+            TraumaDecayCurve::EaseOut => (self.trauma.sqrt() - delta).max(0.0).powi(2),
+        };
+        self.trauma = trauma.max(self.sustained_trauma)
     }
 
+    /// The sum of all noise octaves, normalized to stay in the range of a single octave.
     fn noise_value(&self, stack: u32) -> f32 {
-        simplex_noise_2d_seeded(
-            Vec2::new(self.trauma * self.noise_strength, 0.0),
-            self.seed + stack as f32,
-        )
+        let mut value = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut total_amplitude = 0.0;
+        for _ in 0..self.noise_octaves.max(1) {
+            value += amplitude
+                * (self.noise_fn)(
+                    Vec2::new(self.trauma * self.noise_strength * frequency, 0.0),
+                    self.seed + stack as f32,
+                );
+            total_amplitude += amplitude;
+            amplitude *= self.noise_persistence;
+            frequency *= 2.0;
+        }
+        value / total_amplitude
+    }
+}
+
+/// Clamp `pos` so that an area with the given `half_size` centered on it stays inside `bound`.
+/// On axes where the area is larger than the bound, `pos` is centered on the bound instead.
+fn clamp_pos(pos: Vec2, half_size: Vec2, bound: Aabb2d) -> Vec2 {
+    let min = bound.min + half_size;
+    let max = bound.max - half_size;
+    let center = (bound.min + bound.max) / 2.0;
+    Vec2::new(
+        if min.x <= max.x {
+            pos.x.clamp(min.x, max.x)
+        } else {
+            center.x
+        },
+        if min.y <= max.y {
+            pos.y.clamp(min.y, max.y)
+        } else {
+            center.y
+        },
+    )
+}
+
+/// Add this to the entity the camera should follow, usually the player.
+/// The target of the `CameraShake` is updated in `CameraSystem::TargetUpdate`,
+/// smoothed by `CameraShake::set_follow_smoothing`.
+/// There should only be one entity with this component and it should not have a parent
+/// (its `Transform` is used), don't use it if you update the target yourself.
+#[derive(Component)]
+pub struct CameraTarget;
+
+/// Add this to entities that `fit_camera_bound` should ignore,
+/// for example far-off background props that would balloon the bound.
+#[derive(Component)]
+pub struct ExcludeFromCameraBounds;
+
+/// Sets the camera bound to the `Aabb2d` enclosing the translations
+/// of all entities with the component `M`, or removes the bound if there are none.
+/// Entities with `ExcludeFromCameraBounds` are skipped.
+///
+/// Add this system to your app (in `CameraSystem::TargetUpdate` for example)
+/// to automatically fit the camera bounds to a room.
+/// Note that only the translations are taken into account, not the sizes of the entities.
+pub fn fit_camera_bound<M: Component>(
+    q_transforms: Query<&GlobalTransform, (With<M>, Without<ExcludeFromCameraBounds>)>,
+    mut shake: ResMut<CameraShake>,
+) {
+    shake.set_bound(aabb_enclosing(
+        q_transforms.iter().map(|t| t.translation().truncate()),
+    ));
+}
+
+/// Only update the camera transform and decay the trauma while in the given state.
+/// In all other states the camera keeps its last transform,
+/// which prevents it from drifting in menus for example.
+///
+/// You can call this multiple times, in which case
+/// the camera only runs if all of the given states are active.
+pub fn run_camera_in_state<S: States>(app: &mut App, state: S) {
+    app.configure_sets(Update, TraumaDecaySet.run_if(in_state(state.clone())))
+        .configure_sets(
+            PostUpdate,
+            CameraSystem::TransformUpdate.run_if(in_state(state)),
+        );
+}
+
+fn decay_shake_trauma(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut ev_shake_ended: EventWriter<ShakeEndedEvent>,
+    mut was_shaking: Local<bool>,
+) {
+    let unfocused = q_window.get_single().is_ok_and(|window| !window.focused);
+    if !(unfocused && shake.pause_decay_when_unfocused) {
+        let delta = time.delta_seconds().min(MAX_TRAUMA_DECAY_DELTA);
+        shake.rise_trauma(delta);
+        if !shake.is_shake_frozen() {
+            shake.reduce_trauma(delta);
+        }
     }
+
+    let shaking = shake.trauma > 0.0 || shake.pending_trauma > 0.0;
+    if *was_shaking && !shaking {
+        ev_shake_ended.send(ShakeEndedEvent);
+    }
+    *was_shaking = shaking;
 }
 
-fn decay_shake_trauma(time: Res<Time>, mut shake: ResMut<CameraShake>) {
-    shake.reduce_trauma(time.delta_seconds());
+fn limit_absent_camera_trauma(
+    mut shake: ResMut<CameraShake>,
+    q_camera: Query<(), With<MainCamera>>,
+) {
+    if !q_camera.is_empty() {
+        return;
+    }
+    let max_trauma = match shake.absent_camera_trauma {
+        AbsentCameraTrauma::Hold(max_trauma) => max_trauma.clamp(0.0, 1.0),
+        AbsentCameraTrauma::Clear => 0.0,
+    };
+    if shake.trauma + shake.pending_trauma > max_trauma {
+        shake.trauma = shake.trauma.min(max_trauma);
+        shake.pending_trauma = max_trauma - shake.trauma;
+    }
 }
 
-fn update_camera(mut q_camera: Query<&mut Transform, With<MainCamera>>, shake: Res<CameraShake>) {
-    let mut transform = match q_camera.get_single_mut() {
+fn follow_camera_target(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    q_target: Query<(&Transform, Ref<CameraTarget>)>,
+) {
+    let Ok((transform, camera_target)) = q_target.get_single() else {
+        return;
+    };
+    let position = transform.translation.truncate();
+
+    if shake.follow_smoothing <= 0.0 || (camera_target.is_added() && shake.snap_to_new_target) {
+        shake.target = position;
+        return;
+    }
+    let catch_up = if shake.catch_up_distance > 0.0 {
+        let excess = shake.target.distance(position) / shake.catch_up_distance - 1.0;
+        1.0 + (shake.catch_up_multiplier - 1.0) * excess.clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+    let blend = 1.0 - (-shake.follow_smoothing * catch_up * time.delta_seconds()).exp();
+    shake.target = shake.target.lerp(position, blend);
+}
+
+fn update_camera(
+    time: Res<Time>,
+    mut q_camera: Query<(&mut Transform, &OrthographicProjection), With<MainCamera>>,
+    mut shake: ResMut<CameraShake>,
+    mut final_transform: ResMut<FinalCameraTransform>,
+    mut ev_camera_moved: EventWriter<CameraMovedEvent>,
+    mut ev_camera_pan_finished: EventWriter<CameraPanFinishedEvent>,
+) {
+    let (mut transform, projection) = match q_camera.get_single_mut() {
         Ok(t) => t,
         Err(_) => return,
     };
 
-    let translation_offset = Vec3::new(shake.noise_value(0), shake.noise_value(1), 0.0)
-        * shake.trauma.powi(2)
-        * shake.translation_shake_strength;
-    let rotation_offset = Quat::from_rotation_z(
-        (shake.noise_value(2) * shake.trauma.powi(2) * shake.rotation_shake_strength).to_radians(),
-    );
+    let look_ahead_goal = shake.look_ahead_direction.normalize_or_zero() * shake.look_ahead;
+    let look_ahead_blend = 1.0 - (-shake.look_ahead_smoothing * time.delta_seconds()).exp();
+    shake.look_ahead_offset = shake
+        .look_ahead_offset
+        .lerp(look_ahead_goal, look_ahead_blend);
+
+    shake.tick_bound_transition(time.delta_seconds());
+    let pan_position = shake.pan.as_mut().map(|pan| {
+        pan.elapsed += time.delta_seconds();
+        pan.position()
+    });
+    if let Some(pan) = shake.pan.filter(|pan| pan.elapsed >= pan.duration) {
+        shake.pan = None;
+        shake.target = pan.to;
+        ev_camera_pan_finished.send(CameraPanFinishedEvent);
+    }
+
+    let unclamped_target =
+        pan_position.unwrap_or(shake.target) + shake.target_offset + shake.look_ahead_offset;
+    let target = match shake.effective_bound() {
+        Some(bound) => clamp_pos(unclamped_target, projection.area.half_size(), bound),
+        None => unclamped_target,
+    };
+    shake.clamped = target.cmpne(unclamped_target);
+
+    shake.tick_shake_scales(time.delta_seconds());
+    if shake.is_shake_frozen() {
+        shake.freeze_timer -= time.delta_seconds();
+    } else {
+        let shake_scale = shake.shake_scale();
+        shake.translation_offset = Vec3::new(shake.noise_value(0), shake.noise_value(1), 0.0)
+            * shake.trauma.powf(shake.trauma_exponent)
+            * shake.translation_shake_strength
+            * shake_scale;
+        shake.rotation_offset = shake.noise_value(2)
+            * shake.trauma.powf(shake.trauma_exponent)
+            * shake.rotation_shake_strength
+            * shake_scale;
+    }
+
+    let previous_translation = transform.translation.truncate();
+    let camera_z = shake.camera_z.unwrap_or(transform.translation.z);
+    match shake.locked {
+        Some(position) => {
+            transform.translation = position.extend(camera_z);
+            transform.rotation = Quat::IDENTITY;
+        }
+        None => {
+            transform.translation = target.extend(camera_z) + shake.translation_offset;
+            transform.rotation = Quat::from_rotation_z(shake.rotation_offset.to_radians());
+        }
+    }
+
+    final_transform.translation = transform.translation;
+    final_transform.rotation = transform.rotation;
 
-    transform.translation = shake.target.extend(transform.translation.z) + translation_offset;
-    transform.rotation = rotation_offset;
+    let translation = transform.translation.truncate();
+    let delta = translation - previous_translation;
+    if delta.length_squared() > CAMERA_MOVED_EPSILON.powi(2) {
+        ev_camera_moved.send(CameraMovedEvent { translation, delta });
+    }
+}
+
+fn update_audio_listener_anchor(
+    shake: Res<CameraShake>,
+    q_camera: Query<&Transform, (With<MainCamera>, Without<AudioListenerAnchor>)>,
+    mut q_anchors: Query<&mut Transform, With<AudioListenerAnchor>>,
+) {
+    let Ok(camera_transform) = q_camera.get_single() else {
+        return;
+    };
+    // A locked camera doesn't shake, so its translation has no offset to remove.
+    let offset = match shake.locked {
+        Some(_) => Vec3::ZERO,
+        None => shake.translation_offset,
+    };
+    for mut transform in &mut q_anchors {
+        transform.translation = camera_transform.translation - offset;
+    }
 }
 
 pub struct CameraShakePlugin;
@@ -129,7 +946,17 @@ pub struct CameraShakePlugin;
 impl Plugin for CameraShakePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CameraShake>()
-            .add_systems(Update, (decay_shake_trauma,))
+            .init_resource::<FinalCameraTransform>()
+            .add_event::<CameraMovedEvent>()
+            .add_event::<CameraPanFinishedEvent>()
+            .add_event::<ShakeEndedEvent>()
+            .add_systems(
+                Update,
+                (
+                    decay_shake_trauma.in_set(TraumaDecaySet),
+                    limit_absent_camera_trauma.after(TraumaDecaySet),
+                ),
+            )
             .configure_sets(
                 PostUpdate,
                 (CameraSystem::TargetUpdate, CameraSystem::TransformUpdate)
@@ -139,7 +966,586 @@ impl Plugin for CameraShakePlugin {
             )
             .add_systems(
                 PostUpdate,
-                update_camera.in_set(CameraSystem::TransformUpdate),
+                follow_camera_target.in_set(CameraSystem::TargetUpdate),
+            )
+            .add_systems(
+                PostUpdate,
+                (update_camera, update_audio_listener_anchor)
+                    .chain()
+                    .in_set(CameraSystem::TransformUpdate),
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, CameraShakePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                1.0 / 60.0,
+            )));
+        app.world_mut().spawn((
+            Transform::default(),
+            OrthographicProjection::default(),
+            MainCamera,
+        ));
+        app
+    }
+
+    fn anchor_translation(app: &mut App) -> Vec3 {
+        app.world_mut()
+            .query_filtered::<&Transform, With<AudioListenerAnchor>>()
+            .single(app.world())
+            .translation
+    }
+
+    fn camera_translation(app: &mut App) -> Vec2 {
+        app.world_mut()
+            .query_filtered::<&Transform, With<MainCamera>>()
+            .single(app.world())
+            .translation
+            .truncate()
+    }
+
+    #[test]
+    fn pans_with_target_offset() {
+        let mut app = app();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            0.25,
+        )));
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .set_target_offset(Vec2::new(0.0, 10.0));
+        // The first update has a delta of zero.
+        app.update();
+        assert_eq!(camera_translation(&mut app), Vec2::new(0.0, 10.0));
+
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .pan_to(Vec2::new(100.0, 0.0), 1.0, |t| t);
+        for x in [25.0, 50.0, 75.0, 100.0] {
+            app.update();
+            assert!(camera_translation(&mut app).abs_diff_eq(Vec2::new(x, 10.0), 1e-4));
+        }
+        assert!(!app.world().resource::<CameraShake>().is_panning());
+        assert!(!app
+            .world()
+            .resource::<Events<CameraPanFinishedEvent>>()
+            .is_empty());
+
+        app.update();
+        assert!(camera_translation(&mut app).abs_diff_eq(Vec2::new(100.0, 10.0), 1e-4));
+    }
+
+    #[test]
+    fn audio_listener_anchor_ignores_shake() {
+        let mut app = app();
+        app.world_mut()
+            .spawn((Transform::default(), AudioListenerAnchor));
+        let mut shake = app.world_mut().resource_mut::<CameraShake>();
+        shake.update_target(Vec2::new(10.0, 20.0));
+        shake.add_trauma(1.0);
+
+        for _ in 0..3 {
+            app.update();
+            assert!(app.world().resource::<CameraShake>().translation_offset != Vec3::ZERO);
+            assert!(anchor_translation(&mut app)
+                .truncate()
+                .abs_diff_eq(Vec2::new(10.0, 20.0), 1e-4));
+        }
+    }
+
+    #[test]
+    fn audio_listener_anchor_follows_locked_camera() {
+        let mut app = app();
+        app.world_mut()
+            .spawn((Transform::default(), AudioListenerAnchor));
+        let mut shake = app.world_mut().resource_mut::<CameraShake>();
+        shake.update_target(Vec2::new(10.0, 20.0));
+        shake.add_trauma(1.0);
+        shake.lock(Vec2::new(-5.0, 5.0));
+
+        for _ in 0..3 {
+            app.update();
+            assert!(app.world().resource::<CameraShake>().translation_offset != Vec3::ZERO);
+            assert!(anchor_translation(&mut app)
+                .truncate()
+                .abs_diff_eq(Vec2::new(-5.0, 5.0), 1e-4));
+        }
+    }
+
+    #[test]
+    fn set_trauma_is_absolute_and_clamped() {
+        let mut shake = CameraShake::default();
+        shake.add_trauma(0.2);
+        shake.set_trauma(0.7);
+        assert_eq!(shake.trauma(), 0.7);
+
+        shake.set_trauma(0.1);
+        assert_eq!(shake.trauma(), 0.1);
+        shake.set_trauma(2.0);
+        assert_eq!(shake.trauma(), 1.0);
+        shake.set_trauma(-1.0);
+        assert_eq!(shake.trauma(), 0.0);
+    }
+
+    #[test]
+    fn sustained_trauma_holds_until_released() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .set_sustained_trauma(0.4);
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .add_trauma(0.3);
+
+        for _ in 0..120 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<CameraShake>().trauma(), 0.4);
+
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .release_sustained_trauma();
+        for _ in 0..30 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<CameraShake>().trauma(), 0.0);
+    }
+
+    #[test]
+    fn decay_curves() {
+        let decayed = |decay_curve, delta| {
+            let mut shake = CameraShake::default();
+            shake.set_decay_curve(decay_curve);
+            shake.set_trauma(1.0);
+            shake.reduce_trauma(delta);
+            shake.trauma()
+        };
+
+        assert_eq!(decayed(TraumaDecayCurve::Linear, 0.25), 0.75);
+        assert_eq!(decayed(TraumaDecayCurve::Exponential(0.5), 1.0), 0.5);
+        assert_eq!(decayed(TraumaDecayCurve::EaseOut, 0.5), 0.25);
+        // All curves but the exponential one reach zero within a second.
+        assert_eq!(decayed(TraumaDecayCurve::Linear, 1.0), 0.0);
+        assert_eq!(decayed(TraumaDecayCurve::EaseOut, 1.0), 0.0);
+        assert_eq!(decayed(TraumaDecayCurve::Exponential(0.5), 10.0), 0.0);
+    }
+
+    #[test]
+    fn trauma_rises_at_rise_rate() {
+        let mut shake = CameraShake::default();
+        shake.set_trauma_rise_rate(Some(2.0));
+        shake.add_trauma(0.5);
+        shake.add_trauma(0.5);
+        assert_eq!(shake.trauma(), 0.0);
+
+        shake.rise_trauma(0.25);
+        assert_eq!(shake.trauma(), 0.5);
+        shake.rise_trauma(0.25);
+        assert_eq!(shake.trauma(), 1.0);
+
+        // Disabling the rise rate applies pending trauma right away.
+        shake.set_trauma(0.0);
+        shake.add_trauma(0.6);
+        shake.set_trauma_rise_rate(None);
+        assert_eq!(shake.trauma(), 0.6);
+    }
+
+    #[test]
+    fn sends_shake_ended_once_trauma_is_zero() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .add_trauma(0.1);
+
+        let mut ended = 0;
+        for _ in 0..30 {
+            app.update();
+            let events = app.world().resource::<Events<ShakeEndedEvent>>();
+            let shaking = app.world().resource::<CameraShake>().trauma() > 0.0;
+            ended += events.iter_current_update_events().count();
+            assert_eq!(ended, usize::from(!shaking));
+        }
+        assert_eq!(ended, 1);
+    }
+
+    #[test]
+    fn snaps_to_new_camera_target() {
+        for snap in [true, false] {
+            let mut app = app();
+            let mut shake = app.world_mut().resource_mut::<CameraShake>();
+            shake.set_follow_smoothing(1.0);
+            shake.set_snap_to_new_target(snap);
+            app.world_mut()
+                .spawn((Transform::from_xyz(100.0, 0.0, 0.0), CameraTarget));
+            app.update();
+            app.update();
+
+            let target = app.world().resource::<CameraShake>().target;
+            assert_eq!(target == Vec2::new(100.0, 0.0), snap);
+        }
+    }
+
+    #[test]
+    fn pauses_decay_while_window_is_unfocused() {
+        let mut app = app();
+        let window = app
+            .world_mut()
+            .spawn((
+                Window {
+                    focused: false,
+                    ..default()
+                },
+                PrimaryWindow,
+            ))
+            .id();
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .add_trauma(0.5);
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(app.world().resource::<CameraShake>().trauma(), 0.5);
+
+        app.world_mut().get_mut::<Window>(window).unwrap().focused = true;
+        app.update();
+        app.update();
+        assert!(app.world().resource::<CameraShake>().trauma() < 0.5);
+    }
+
+    #[test]
+    fn catch_up_boosts_follow_speed() {
+        let followed = |catch_up_multiplier| {
+            let mut app = app();
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                0.1,
+            )));
+            let mut shake = app.world_mut().resource_mut::<CameraShake>();
+            shake.set_follow_smoothing(1.0);
+            shake.set_follow_catch_up(10.0, catch_up_multiplier);
+            let target = app
+                .world_mut()
+                .spawn((Transform::default(), CameraTarget))
+                .id();
+            // The first update has a delta of zero and snaps to the new target.
+            app.update();
+            app.world_mut()
+                .get_mut::<Transform>(target)
+                .unwrap()
+                .translation
+                .x = 100.0;
+            app.update();
+            app.world().resource::<CameraShake>().target.x
+        };
+
+        // Far beyond the catch-up distance the full multiplier applies.
+        let expected = |multiplier: f32| 100.0 * (1.0 - (-multiplier * 0.1).exp());
+        assert!((followed(1.0) - expected(1.0)).abs() < 1e-3);
+        assert!((followed(5.0) - expected(5.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn locked_camera_ignores_target_and_shake() {
+        let mut app = app();
+        let mut shake = app.world_mut().resource_mut::<CameraShake>();
+        shake.lock(Vec2::new(-5.0, 5.0));
+        shake.update_target(Vec2::new(10.0, 20.0));
+        shake.add_trauma(1.0);
+        app.update();
+
+        let transform = app
+            .world_mut()
+            .query_filtered::<&Transform, With<MainCamera>>()
+            .single(app.world());
+        assert_eq!(transform.translation.truncate(), Vec2::new(-5.0, 5.0));
+        assert_eq!(transform.rotation, Quat::IDENTITY);
+
+        app.world_mut().resource_mut::<CameraShake>().unlock();
+        app.world_mut().resource_mut::<CameraShake>().clear_trauma();
+        app.update();
+        assert_eq!(camera_translation(&mut app), Vec2::new(10.0, 20.0));
+    }
+
+    #[test]
+    fn reset_restores_defaults() {
+        let mut app = app();
+        let mut shake = app.world_mut().resource_mut::<CameraShake>();
+        shake.update_target(Vec2::new(10.0, 20.0));
+        shake.set_bound(Some(Aabb2d::new(Vec2::ZERO, Vec2::splat(100.0))));
+        shake.set_sustained_trauma(0.5);
+        shake.add_trauma(1.0);
+        shake.set_trauma_exponent(1.0);
+        shake.set_noise_strength(3.0);
+        app.update();
+        assert!(app.world().resource::<CameraShake>().translation_offset != Vec3::ZERO);
+
+        let mut shake = app.world_mut().resource_mut::<CameraShake>();
+        shake.reset();
+        assert_eq!(shake.trauma(), 0.0);
+        assert_eq!(shake.sustained_trauma, 0.0);
+        assert_eq!(shake.translation_offset, Vec3::ZERO);
+        assert_eq!(shake.rotation_offset, 0.0);
+        assert!(shake.bound().is_none());
+        assert_eq!(shake.shake_config(), ShakeConfig::default());
+
+        app.update();
+        let transform = app
+            .world_mut()
+            .query_filtered::<&Transform, With<MainCamera>>()
+            .single(app.world());
+        assert_eq!(transform.translation.truncate(), Vec2::ZERO);
+        assert_eq!(transform.rotation, Quat::IDENTITY);
+    }
+
+    #[test]
+    fn frozen_shake_holds_its_offset() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .add_trauma(1.0);
+        app.update();
+        let offset = app.world().resource::<CameraShake>().translation_offset;
+        assert!(offset != Vec3::ZERO);
+
+        let mut shake = app.world_mut().resource_mut::<CameraShake>();
+        shake.freeze_shake(0.1);
+        // Changing the trauma doesn't move the camera while frozen.
+        shake.set_trauma(0.5);
+        for _ in 0..5 {
+            app.update();
+            let shake = app.world().resource::<CameraShake>();
+            assert!(shake.is_shake_frozen());
+            assert_eq!(shake.trauma(), 0.5);
+            assert_eq!(shake.translation_offset, offset);
+            assert_eq!(camera_translation(&mut app), offset.truncate());
+        }
+
+        for _ in 0..3 {
+            app.update();
+        }
+        let shake = app.world().resource::<CameraShake>();
+        assert!(!shake.is_shake_frozen());
+        assert!(shake.trauma() < 0.5);
+        assert!(shake.translation_offset != offset);
+    }
+
+    #[test]
+    fn clamped_flags_follow_the_bound() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .set_bound(Some(Aabb2d {
+                min: Vec2::ZERO,
+                max: Vec2::splat(100.0),
+            }));
+
+        for (target, clamped) in [
+            (Vec2::new(200.0, 50.0), BVec2::new(true, false)),
+            (Vec2::new(50.0, -200.0), BVec2::new(false, true)),
+            (Vec2::new(-50.0, 150.0), BVec2::TRUE),
+            (Vec2::new(50.0, 50.0), BVec2::FALSE),
+        ] {
+            app.world_mut()
+                .resource_mut::<CameraShake>()
+                .update_target(target);
+            app.update();
+            assert_eq!(app.world().resource::<CameraShake>().clamped(), clamped);
+        }
+    }
+
+    #[test]
+    fn positive_rotation_shake_is_counterclockwise() {
+        let rotated_x = |strength| {
+            let mut app = app();
+            let mut shake = app.world_mut().resource_mut::<CameraShake>();
+            shake.set_noise_fn(|_, _| 1.0);
+            shake.set_rotation_shake_strength(strength);
+            shake.add_trauma(1.0);
+            // The first update has a delta of zero, so the trauma is still `1.0`.
+            app.update();
+            let transform = app
+                .world_mut()
+                .query_filtered::<&Transform, With<MainCamera>>()
+                .single(app.world());
+            transform.rotation * Vec3::X
+        };
+
+        let expected = Vec3::new(2.5f32.to_radians().cos(), 2.5f32.to_radians().sin(), 0.0);
+        assert!(rotated_x(2.5).abs_diff_eq(expected, 1e-5));
+        assert!(rotated_x(-2.5).abs_diff_eq(expected * Vec3::new(1.0, -1.0, 1.0), 1e-5));
+    }
+
+    #[test]
+    fn shake_scale_is_restored_after_its_duration() {
+        let mut app = app();
+        let mut shake = app.world_mut().resource_mut::<CameraShake>();
+        shake.set_noise_fn(|_, _| 1.0);
+        shake.set_sustained_trauma(1.0);
+        shake.push_shake_scale(2.0, 0.1);
+        app.update();
+        assert_eq!(camera_translation(&mut app), Vec2::splat(30.0));
+        assert_eq!(app.world().resource::<CameraShake>().rotation_offset, 5.0);
+
+        for _ in 0..10 {
+            app.update();
+        }
+        let shake = app.world().resource::<CameraShake>();
+        assert_eq!(shake.shake_scale(), 1.0);
+        assert_eq!(shake.rotation_offset, 2.5);
+        assert_eq!(camera_translation(&mut app), Vec2::splat(15.0));
+    }
+
+    #[test]
+    fn trauma_exponent_shapes_the_amplitude() {
+        for exponent in [1.0, 2.0, 3.0] {
+            let mut app = app();
+            let mut shake = app.world_mut().resource_mut::<CameraShake>();
+            shake.set_noise_fn(|_, _| 1.0);
+            shake.set_trauma_exponent(exponent);
+            shake.set_trauma(0.5);
+            app.update();
+
+            let amplitude = 0.5f32.powf(exponent);
+            let shake = app.world().resource::<CameraShake>();
+            assert_eq!(
+                shake.translation_offset,
+                Vec3::new(15.0, 15.0, 0.0) * amplitude
+            );
+            assert_eq!(shake.rotation_offset, 2.5 * amplitude);
+        }
+    }
+
+    #[test]
+    fn trauma_without_camera_is_held_at_the_cap() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, CameraShakePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                1.0 / 60.0,
+            )));
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .add_trauma(1.0);
+        // The first update has a delta of zero, so only the cap applies.
+        app.update();
+        assert_eq!(app.world().resource::<CameraShake>().trauma(), 0.3);
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .add_trauma(1.0);
+        app.update();
+        assert_eq!(app.world().resource::<CameraShake>().trauma(), 0.3);
+
+        app.world_mut().spawn((
+            Transform::default(),
+            OrthographicProjection::default(),
+            MainCamera,
+        ));
+        app.update();
+        let trauma = app.world().resource::<CameraShake>().trauma();
+        assert!(trauma > 0.0 && trauma <= 0.3);
+
+        let mut shake = app.world_mut().resource_mut::<CameraShake>();
+        shake.set_absent_camera_trauma(AbsentCameraTrauma::Clear);
+        let camera = app
+            .world_mut()
+            .query_filtered::<Entity, With<MainCamera>>()
+            .single(app.world());
+        app.world_mut().despawn(camera);
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .add_trauma(1.0);
+        app.update();
+        assert_eq!(app.world().resource::<CameraShake>().trauma(), 0.0);
+    }
+
+    #[test]
+    fn applies_camera_z_while_shaking() {
+        let mut app = app();
+        let mut shake = app.world_mut().resource_mut::<CameraShake>();
+        shake.set_camera_z(Some(50.0));
+        shake.set_sustained_trauma(1.0);
+        let camera_z = |app: &mut App| {
+            app.world_mut()
+                .query_filtered::<&Transform, With<MainCamera>>()
+                .single(app.world())
+                .translation
+                .z
+        };
+        for _ in 0..3 {
+            app.update();
+            assert_eq!(camera_z(&mut app), 50.0);
+        }
+
+        // Without a camera z, the camera keeps its current one.
+        app.world_mut()
+            .resource_mut::<CameraShake>()
+            .set_camera_z(None);
+        app.update();
+        assert_eq!(camera_z(&mut app), 50.0);
+    }
+
+    #[test]
+    fn octave_noise_is_deterministic_for_a_seed() {
+        let noise = |seed: f32, octaves: u32| {
+            let mut shake = CameraShake::default();
+            shake.set_noise_octaves(octaves, 0.5);
+            shake.set_trauma(0.7);
+            shake.seed = seed;
+            [0, 1, 2].map(|stack| shake.noise_value(stack))
+        };
+
+        assert_eq!(noise(42.0, 4), noise(42.0, 4));
+        assert_ne!(noise(42.0, 4), noise(43.0, 4));
+        assert_ne!(noise(42.0, 4), noise(42.0, 1));
+        assert!(noise(42.0, 4).iter().all(|value| value.abs() <= 1.0));
+    }
+
+    #[derive(Component)]
+    struct RoomTile;
+
+    #[test]
+    fn fits_camera_bound_to_marked_entities() {
+        let mut app = app();
+        app.add_systems(Update, fit_camera_bound::<RoomTile>);
+        app.update();
+        assert!(app.world().resource::<CameraShake>().bound().is_none());
+
+        for (x, y) in [(-10.0, 5.0), (30.0, -20.0), (0.0, 40.0)] {
+            app.world_mut()
+                .spawn((GlobalTransform::from_xyz(x, y, 0.0), RoomTile));
+        }
+        app.world_mut()
+            .spawn(GlobalTransform::from_xyz(500.0, 500.0, 0.0));
+        app.update();
+        let bound = app.world().resource::<CameraShake>().bound().unwrap();
+        assert_eq!(bound.min, Vec2::new(-10.0, -20.0));
+        assert_eq!(bound.max, Vec2::new(30.0, 40.0));
+    }
+
+    #[test]
+    fn excluded_entities_dont_expand_the_camera_bound() {
+        let mut app = app();
+        app.add_systems(Update, fit_camera_bound::<RoomTile>);
+        for (x, y) in [(0.0, 0.0), (20.0, 10.0)] {
+            app.world_mut()
+                .spawn((GlobalTransform::from_xyz(x, y, 0.0), RoomTile));
+        }
+        app.world_mut().spawn((
+            GlobalTransform::from_xyz(1000.0, -1000.0, 0.0),
+            RoomTile,
+            ExcludeFromCameraBounds,
+        ));
+        app.update();
+
+        let bound = app.world().resource::<CameraShake>().bound().unwrap();
+        assert_eq!(bound.min, Vec2::ZERO);
+        assert_eq!(bound.max, Vec2::new(20.0, 10.0));
+    }
+}
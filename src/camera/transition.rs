@@ -0,0 +1,164 @@
+use bevy::{prelude::*, render::view::RenderLayers};
+
+use super::MainCamera;
+
+/// Size of the overlay, big enough to cover the view at any reasonable zoom.
+const OVERLAY_SIZE: f32 = 100_000.0;
+/// Local z of the overlay, right in front of the camera.
+const OVERLAY_Z: f32 = -0.1;
+
+/// Settings of the screen transitions started with `StartScreenTransitionEvent`.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct ScreenTransitionSettings {
+    /// Duration of the fade out in seconds.
+    pub fade_out: f32,
+    /// Duration of the fade in in seconds.
+    pub fade_in: f32,
+    /// The color the screen fades to.
+    pub color: Color,
+}
+
+impl Default for ScreenTransitionSettings {
+    fn default() -> Self {
+        Self {
+            fade_out: 0.5,
+            fade_in: 0.5,
+            color: Color::BLACK,
+        }
+    }
+}
+
+/// Send this `Event` to start a screen transition.
+/// The screen fades out to the color of the `ScreenTransitionSettings`,
+/// then `SceneSwapPoint` is sent, and then the screen fades back in,
+/// after which `TransitionComplete` is sent.
+///
+/// This is ignored while a transition is already running.
+#[derive(Event)]
+pub struct StartScreenTransitionEvent;
+
+/// Sent when the screen is fully covered during a screen transition.
+/// Swap your scene (despawn the old one and spawn the new one) when you receive this.
+#[derive(Event)]
+pub struct SceneSwapPoint;
+
+/// Sent when a screen transition has finished fading back in.
+#[derive(Event)]
+pub struct TransitionComplete;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum TransitionPhase {
+    #[default]
+    Idle,
+    FadeOut(f32),
+    FadeIn(f32),
+}
+
+/// The current phase of the screen transition, with the elapsed time of that phase.
+#[derive(Resource, Default)]
+struct ScreenTransition(TransitionPhase);
+
+/// The sprite that covers the screen during transitions.
+#[derive(Component)]
+struct TransitionOverlay;
+
+fn spawn_transition_overlay(
+    mut commands: Commands,
+    settings: Res<ScreenTransitionSettings>,
+    q_cameras: Query<(Entity, Option<&RenderLayers>), Added<MainCamera>>,
+) {
+    for (camera, render_layers) in &q_cameras {
+        // The overlay must be on the layers of the camera, otherwise it's not visible.
+        let render_layers = render_layers.cloned().unwrap_or_default();
+        commands.entity(camera).with_children(|parent| {
+            parent.spawn((
+                render_layers,
+                TransitionOverlay,
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: settings.color.with_alpha(0.0),
+                        custom_size: Some(Vec2::splat(OVERLAY_SIZE)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, OVERLAY_Z),
+                    ..default()
+                },
+            ));
+        });
+    }
+}
+
+fn start_screen_transition(mut transition: ResMut<ScreenTransition>) {
+    if transition.0 == TransitionPhase::Idle {
+        transition.0 = TransitionPhase::FadeOut(0.0);
+    }
+}
+
+/// The progress of a phase between `0.0..1.0`.
+fn progress(elapsed: f32, duration: f32) -> f32 {
+    if duration > 0.0 {
+        (elapsed / duration).min(1.0)
+    } else {
+        1.0
+    }
+}
+
+fn update_screen_transition(
+    time: Res<Time>,
+    settings: Res<ScreenTransitionSettings>,
+    mut transition: ResMut<ScreenTransition>,
+    mut q_overlays: Query<&mut Sprite, With<TransitionOverlay>>,
+    mut ev_scene_swap_point: EventWriter<SceneSwapPoint>,
+    mut ev_transition_complete: EventWriter<TransitionComplete>,
+) {
+    let alpha = match transition.0 {
+        TransitionPhase::Idle => return,
+        TransitionPhase::FadeOut(elapsed) => {
+            let elapsed = elapsed + time.delta_seconds();
+            let t = progress(elapsed, settings.fade_out);
+            if t >= 1.0 {
+                ev_scene_swap_point.send(SceneSwapPoint);
+                transition.0 = TransitionPhase::FadeIn(0.0);
+            } else {
+                transition.0 = TransitionPhase::FadeOut(elapsed);
+            }
+            t
+        }
+        TransitionPhase::FadeIn(elapsed) => {
+            let elapsed = elapsed + time.delta_seconds();
+            let t = progress(elapsed, settings.fade_in);
+            if t >= 1.0 {
+                ev_transition_complete.send(TransitionComplete);
+                transition.0 = TransitionPhase::Idle;
+            } else {
+                transition.0 = TransitionPhase::FadeIn(elapsed);
+            }
+            1.0 - t
+        }
+    };
+
+    for mut sprite in &mut q_overlays {
+        sprite.color = settings.color.with_alpha(alpha);
+    }
+}
+
+pub struct ScreenTransitionPlugin;
+
+impl Plugin for ScreenTransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenTransitionSettings>()
+            .init_resource::<ScreenTransition>()
+            .add_event::<StartScreenTransitionEvent>()
+            .add_event::<SceneSwapPoint>()
+            .add_event::<TransitionComplete>()
+            .add_systems(
+                Update,
+                (
+                    spawn_transition_overlay,
+                    start_screen_transition.run_if(on_event::<StartScreenTransitionEvent>()),
+                    update_screen_transition,
+                )
+                    .chain(),
+            );
+    }
+}
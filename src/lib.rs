@@ -1,5 +1,8 @@
 #![doc = include_str!("../README.md")]
-#![forbid(unsafe_code, dead_code)]
+#![forbid(unsafe_code)]
+// Only deny dead code, code that is only used with some features enabled
+// can then be allowed locally with `#[cfg_attr(not(feature = "..."), allow(dead_code))]`.
+#![deny(dead_code)]
 #![warn(unused_imports, missing_docs)]
 
 mod audio;
@@ -28,15 +31,47 @@ impl Plugin for RancicPlugin {
 
 /// `use bevy_rancic::prelude::*;` to import common components and plugins.
 pub mod prelude {
-    pub use crate::audio::{GameAudio, PlaySound, SpacialSound};
+    pub use crate::audio::{
+        AudioBus, AudioFadeSettings, AudioStats, AudioSystem, FadeOutAndExitEvent, GameAudio,
+        PauseAudioEvent, PauseChannelEvent, PlaySound, PlaySpacialSound, ResumeAudioEvent,
+        ResumeChannelEvent, SoundChannel, SoundId, SpacialAttenuation, SpacialListenerOverride,
+        SpacialPanning, SpacialRange, SpacialSound, SpacialSoundBuilder, StopSound,
+    };
+    #[cfg(feature = "test-audio")]
+    pub use crate::audio::{AudioRecorder, RecordedAudioCommand};
+    #[cfg(feature = "rumble")]
+    pub use crate::camera::TraumaRumble;
     pub use crate::camera::{
-        CameraShake, CameraSystem, MainCamera, ToggleFullscreenEvent, YSort, YSortChild,
-        YSortStatic, YSortStaticChild, ZoomCameraScaleEvent,
+        fit_camera_bound, main_camera, run_camera_in_state, visible_rect, AbsentCameraTrauma,
+        AudioListenerAnchor, CameraMovedEvent, CameraPanFinishedEvent, CameraProjection,
+        CameraScale, CameraShake, CameraSpawnSettings, CameraSystem, CameraTarget, CameraView,
+        CameraZoom, ExcludeFromCameraBounds, FinalCameraTransform, MainCamera, SceneSwapPoint,
+        ScreenTransitionSettings, ScreenshotSettings, SetCameraScaleEvent, ShakeConfig,
+        ShakeEndedEvent, StartScreenTransitionEvent, ToggleFullscreenEvent, TransitionComplete,
+        TraumaDecayCurve, YSort, YSortChild, YSortScale, YSortSettings, YSortSpriteBundle,
+        YSortStatic, YSortStaticChild, YSortStaticDirty, YSortSystem, ZoomCameraScaleEvent,
+    };
+    pub use crate::physics::{
+        entities_in_radius, AreaEntered, AreaExited, AreaTrigger, CollisionShake, FixedTimestep,
+        PhysicsFeel, PhysicsSchedule, PhysicsSettings,
     };
     pub use crate::utils::{
-        debug::{DebugState, ToggleDebugStateEvent},
-        quat_from_vec2, quat_from_vec3, COLLIDER_COLOR_BLACK, COLLIDER_COLOR_TRANSPARENT,
-        COLLIDER_COLOR_WHITE, COLLISION_GROUPS_NONE,
+        aabb_enclosing, aabb_from_center_size, aabb_from_transform, angle_from_vec2,
+        debug::{
+            CycleSoloSpacialEmitterEvent, DebugColliderFilter, DebugFlag, DebugFlags, DebugState,
+            DumpRancicSettingsEvent, FrameStepper, FrameStepperSettings, StepFrameEvent,
+            ToggleAudioStatsDebugEvent, ToggleColliderDebugEvent, ToggleDebugStateEvent,
+            ToggleFrameStepperEvent, ToggleHearingRangeDebugEvent, ToggleSpacialAudioDebugEvent,
+            TraumaBreakdown,
+        },
+        ease_in_out,
+        layers::{
+            GameLayer, COLLISION_GROUPS_ENEMY, COLLISION_GROUPS_PICKUP, COLLISION_GROUPS_PLAYER,
+            COLLISION_GROUPS_PROJECTILE, COLLISION_GROUPS_WALL, GROUP_ENEMY, GROUP_PICKUP,
+            GROUP_PLAYER, GROUP_PROJECTILE, GROUP_WALL,
+        },
+        quat_from_vec2, quat_from_vec3, rotate_vec2, COLLIDER_COLOR_BLACK,
+        COLLIDER_COLOR_TRANSPARENT, COLLIDER_COLOR_WHITE, COLLISION_GROUPS_NONE,
     };
     pub use crate::RancicPlugin;
 }
@@ -1,22 +1,438 @@
+mod trigger;
+
 use bevy::prelude::*;
 use bevy_rapier2d::{prelude::*, rapier::dynamics::IntegrationParameters};
 
+use crate::camera::CameraShake;
+
+pub use trigger::{AreaEntered, AreaExited, AreaTrigger};
+
+/// Settings for the rapier physics.
+/// They are applied once on startup, so insert this resource before that.
+/// If your game already changed rapier's `RapierConfiguration` or `IntegrationParameters`
+/// by then, those are kept and the settings for them are skipped (with a warning in debug builds).
+///
+/// This crate doesn't add `RapierPhysicsPlugin` itself, so you choose its schedule.
+/// If your gameplay runs in `FixedUpdate`, add `PhysicsSchedule::FixedUpdate.rapier_plugin()`
+/// to keep physics and gameplay in lockstep, see `PhysicsSchedule`.
+/// The camera and `YSort` systems run in `PostUpdate` after `PhysicsSet::Writeback`,
+/// which only orders them if rapier runs in `PostUpdate` (the default).
+/// With any earlier schedule (like `FixedUpdate`) the physics has already run
+/// when they do, so they see the latest positions either way.
+#[derive(Resource, Default, Debug)]
+pub struct PhysicsSettings {
+    /// Run rapier with a fixed timestep instead of its default variable timestep.
+    /// This sets rapier's `TimestepMode` to `TimestepMode::Fixed`.
+    /// Useful for deterministic games (networking, replays).
+    pub fixed_timestep: Option<FixedTimestep>,
+    /// How contacts between colliders are resolved, `PhysicsFeel::Snappy` by default.
+    pub feel: PhysicsFeel,
+}
+
+/// Presets for how rapier resolves penetrations between colliders,
+/// so that you can pick a feel without tuning rapier's `IntegrationParameters`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PhysicsFeel {
+    /// Penetrations are resolved immediately and without damping.
+    /// Colliders feel hard, which suits most top down games.
+    /// Sets `normalized_max_corrective_velocity` to `f32::MAX`
+    /// and `contact_damping_ratio` to `1.0`.
+    #[default]
+    Snappy,
+    /// Rapier's own defaults.
+    Default,
+    /// Penetrations are resolved slowly and with strong damping.
+    /// Colliders feel squishy and push each other apart gently,
+    /// which suits crowds of enemies for example.
+    /// Sets `normalized_max_corrective_velocity` to `2.0`
+    /// and `contact_damping_ratio` to `20.0`.
+    Soft,
+}
+
+impl PhysicsFeel {
+    /// The `IntegrationParameters` of this preset.
+    pub fn integration_parameters(self) -> IntegrationParameters {
+        match self {
+            PhysicsFeel::Snappy => IntegrationParameters {
+                normalized_max_corrective_velocity: f32::MAX,
+                contact_damping_ratio: 1.0,
+                ..default()
+            },
+            PhysicsFeel::Default => IntegrationParameters::default(),
+            PhysicsFeel::Soft => IntegrationParameters {
+                normalized_max_corrective_velocity: 2.0,
+                contact_damping_ratio: 20.0,
+                ..default()
+            },
+        }
+    }
+}
+
+/// The schedule rapier runs in.
+///
+/// Add the plugin from `PhysicsSchedule::rapier_plugin` to run rapier in that schedule.
+/// The `PhysicsSettings` pick up the schedule from where rapier's systems were added,
+/// so there is nothing else to configure.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PhysicsSchedule {
+    /// Rapier's default, physics steps once per frame with a variable timestep.
+    #[default]
+    PostUpdate,
+    /// Physics steps once per run of `FixedUpdate`, by exactly the `Time<Fixed>` timestep,
+    /// unless `PhysicsSettings::fixed_timestep` is set, which takes precedence.
+    /// The timestep is read once on startup, so set `Time<Fixed>` before that.
+    FixedUpdate,
+}
+
+impl PhysicsSchedule {
+    /// A `RapierPhysicsPlugin` that runs in this schedule,
+    /// with the given pixels per meter.
+    ///
+    /// This is how you choose the schedule, there is no setting for it.
+    /// Adding `RapierPhysicsPlugin` yourself is the same, as long as you either keep
+    /// its default schedule or use `RapierPhysicsPlugin::in_fixed_schedule`.
+    pub fn rapier_plugin(self, pixels_per_meter: f32) -> RapierPhysicsPlugin<NoUserData> {
+        let plugin = RapierPhysicsPlugin::pixels_per_meter(pixels_per_meter);
+        match self {
+            PhysicsSchedule::PostUpdate => plugin,
+            PhysicsSchedule::FixedUpdate => plugin.in_fixed_schedule(),
+        }
+    }
+}
+
+/// A fixed timestep for the physics simulation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedTimestep {
+    /// The duration of a single physics step in seconds.
+    pub dt: f32,
+    /// The number of substeps that each step is split into.
+    pub substeps: usize,
+}
+
+/// Shake the camera on hard collisions, scaled by the force of the impact.
+/// Disabled by default.
+///
+/// This reads rapier's `ContactForceEvent`s, so the colliders that should shake the camera
+/// need `ActiveEvents::CONTACT_FORCE_EVENTS`. Use `ContactForceEventThreshold`
+/// on them to keep rapier from sending events for light touches in the first place.
+#[derive(Resource, Debug)]
+pub struct CollisionShake {
+    /// Whether collisions shake the camera.
+    pub enabled: bool,
+    /// Collisions with a total force magnitude below this don't shake the camera.
+    pub force_threshold: f32,
+    /// The trauma added per unit of force above the `force_threshold`.
+    pub trauma_per_force: f32,
+    /// Only collisions where one of the colliders is a member of these groups
+    /// shake the camera. Colliders without `CollisionGroups` are members of all groups.
+    pub groups: Group,
+}
+
+impl Default for CollisionShake {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            force_threshold: 1000.0,
+            trauma_per_force: 0.0001,
+            groups: Group::ALL,
+        }
+    }
+}
+
+/// All entities whose colliders intersect the circle at `center` with the given `radius`.
+/// Useful for area of effect attacks, aggro ranges or interaction prompts.
+///
+/// Use the `filter` to only find certain layers, for example
+/// `QueryFilter::new().groups(GameLayer::Enemy.collision_groups())`.
+pub fn entities_in_radius(
+    rapier_context: &RapierContext,
+    center: Vec2,
+    radius: f32,
+    filter: QueryFilter,
+) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    rapier_context.intersections_with_shape(
+        center,
+        0.0,
+        &Collider::ball(radius),
+        filter,
+        |entity| {
+            entities.push(entity);
+            true
+        },
+    );
+    entities
+}
+
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, configure_physics);
+        app.add_plugins(trigger::AreaTriggerPlugin)
+            .init_resource::<PhysicsSettings>()
+            .init_resource::<CollisionShake>()
+            .add_systems(Startup, configure_physics)
+            .add_systems(
+                Update,
+                shake_on_collisions.run_if(resource_exists::<Events<ContactForceEvent>>),
+            );
+    }
+}
+
+fn shake_on_collisions(
+    collision_shake: Res<CollisionShake>,
+    mut shake: ResMut<CameraShake>,
+    q_groups: Query<&CollisionGroups>,
+    mut ev_contact_force: EventReader<ContactForceEvent>,
+) {
+    if !collision_shake.enabled {
+        ev_contact_force.clear();
+        return;
+    }
+
+    let is_member = |entity: Entity| {
+        q_groups.get(entity).map_or(true, |groups| {
+            groups.memberships.intersects(collision_shake.groups)
+        })
+    };
+    for ev in ev_contact_force.read() {
+        let excess_force = ev.total_force_magnitude - collision_shake.force_threshold;
+        if excess_force <= 0.0 || !(is_member(ev.collider1) || is_member(ev.collider2)) {
+            continue;
+        }
+        shake.add_trauma(excess_force * collision_shake.trauma_per_force);
     }
 }
 
 fn configure_physics(
-    mut rapier_config: ResMut<RapierConfiguration>,
-    mut rapier_context: ResMut<RapierContext>,
+    settings: Res<PhysicsSettings>,
+    fixed_time: Res<Time<Fixed>>,
+    schedules: Res<Schedules>,
+    rapier_config: Option<ResMut<RapierConfiguration>>,
+    rapier_context: Option<ResMut<RapierContext>>,
 ) {
-    rapier_config.gravity = Vec2::ZERO;
-    rapier_context.integration_parameters = IntegrationParameters {
-        normalized_max_corrective_velocity: f32::MAX,
-        contact_damping_ratio: 1.0,
-        ..default()
+    let (Some(mut rapier_config), Some(mut rapier_context)) = (rapier_config, rapier_context)
+    else {
+        #[cfg(debug_assertions)]
+        warn!(
+            "rapier resources are missing, the `PhysicsSettings` won't be applied. \
+            Add `RapierPhysicsPlugin` to your app."
+        );
+        return;
     };
+
+    // Don't overwrite a configuration the game changed itself,
+    // for example in a `Startup` system that ran before ours.
+    let length_unit = rapier_context.integration_parameters.length_unit;
+    if !is_default_rapier_config(&rapier_config, length_unit) {
+        #[cfg(debug_assertions)]
+        warn!(
+            "`RapierConfiguration` was already changed, \
+            the gravity and timestep of the `PhysicsSettings` won't be applied"
+        );
+    } else {
+        rapier_config.gravity = Vec2::ZERO;
+        let schedule = rapier_schedule(&schedules);
+        apply_timestep(&settings, schedule, &fixed_time, &mut rapier_config);
+    }
+
+    if !is_default_integration_parameters(&rapier_context.integration_parameters) {
+        #[cfg(debug_assertions)]
+        warn!(
+            "rapier's `IntegrationParameters` were already changed, \
+            the `PhysicsFeel` of the `PhysicsSettings` won't be applied"
+        );
+    } else {
+        rapier_context.integration_parameters = IntegrationParameters {
+            length_unit,
+            ..settings.feel.integration_parameters()
+        };
+    }
+}
+
+/// Whether the configuration is still the one that `RapierPhysicsPlugin` inserted.
+fn is_default_rapier_config(rapier_config: &RapierConfiguration, length_unit: f32) -> bool {
+    let default = RapierConfiguration::new(length_unit);
+    rapier_config.gravity == default.gravity && rapier_config.timestep_mode == default.timestep_mode
+}
+
+/// Whether the parameters that the `PhysicsFeel` sets still have rapier's defaults.
+fn is_default_integration_parameters(parameters: &IntegrationParameters) -> bool {
+    let default = IntegrationParameters::default();
+    parameters.normalized_max_corrective_velocity == default.normalized_max_corrective_velocity
+        && parameters.contact_damping_ratio == default.contact_damping_ratio
+}
+
+/// The schedule that rapier's physics step was added to.
+fn rapier_schedule(schedules: &Schedules) -> PhysicsSchedule {
+    let in_fixed_update = schedules.get(FixedUpdate).is_some_and(|schedule| {
+        schedule.graph().system_sets().any(|(_, set, _)| {
+            set.as_dyn_eq()
+                .dyn_eq(PhysicsSet::StepSimulation.as_dyn_eq())
+        })
+    });
+    if in_fixed_update {
+        PhysicsSchedule::FixedUpdate
+    } else {
+        PhysicsSchedule::PostUpdate
+    }
+}
+
+fn apply_timestep(
+    settings: &PhysicsSettings,
+    schedule: PhysicsSchedule,
+    fixed_time: &Time<Fixed>,
+    rapier_config: &mut RapierConfiguration,
+) {
+    match (settings.fixed_timestep, schedule) {
+        (Some(fixed_timestep), _) => {
+            rapier_config.timestep_mode = TimestepMode::Fixed {
+                dt: fixed_timestep.dt,
+                substeps: fixed_timestep.substeps,
+            };
+        }
+        (None, PhysicsSchedule::FixedUpdate) => {
+            rapier_config.timestep_mode = TimestepMode::Fixed {
+                dt: fixed_time.timestep().as_secs_f32(),
+                substeps: 1,
+            };
+        }
+        (None, PhysicsSchedule::PostUpdate) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(schedule: PhysicsSchedule, settings: PhysicsSettings) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, schedule.rapier_plugin(1.0), PhysicsPlugin))
+            .init_resource::<CameraShake>()
+            .insert_resource(settings);
+        app
+    }
+
+    #[test]
+    fn fixed_update_schedule_steps_by_fixed_timestep() {
+        let mut app = app(PhysicsSchedule::FixedUpdate, PhysicsSettings::default());
+        app.insert_resource(Time::<Fixed>::from_hz(50.0));
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<RapierConfiguration>().timestep_mode,
+            TimestepMode::Fixed {
+                dt: 0.02,
+                substeps: 1
+            }
+        );
+    }
+
+    #[test]
+    fn keeps_changed_rapier_config() {
+        let mut app = app(
+            PhysicsSchedule::PostUpdate,
+            PhysicsSettings {
+                fixed_timestep: Some(FixedTimestep {
+                    dt: 0.01,
+                    substeps: 2,
+                }),
+                ..default()
+            },
+        );
+        app.world_mut()
+            .resource_mut::<RapierConfiguration>()
+            .gravity = Vec2::new(0.0, -5.0);
+        app.update();
+
+        let rapier_config = app.world().resource::<RapierConfiguration>();
+        assert_eq!(rapier_config.gravity, Vec2::new(0.0, -5.0));
+        assert!(matches!(
+            rapier_config.timestep_mode,
+            TimestepMode::Variable { .. }
+        ));
+        // The integration parameters weren't changed, so the feel is still applied.
+        assert_eq!(
+            app.world()
+                .resource::<RapierContext>()
+                .integration_parameters
+                .contact_damping_ratio,
+            1.0
+        );
+    }
+
+    #[test]
+    fn post_update_schedule_keeps_variable_timestep() {
+        let mut app = app(PhysicsSchedule::PostUpdate, PhysicsSettings::default());
+        app.update();
+
+        let rapier_config = app.world().resource::<RapierConfiguration>();
+        assert_eq!(rapier_config.gravity, Vec2::ZERO);
+        assert!(matches!(
+            rapier_config.timestep_mode,
+            TimestepMode::Variable { .. }
+        ));
+    }
+
+    #[test]
+    fn hard_collisions_shake_the_camera() {
+        let mut app = app(PhysicsSchedule::PostUpdate, PhysicsSettings::default());
+        app.insert_resource(CollisionShake {
+            enabled: true,
+            groups: Group::GROUP_1,
+            ..default()
+        });
+        app.update();
+        let player = app
+            .world_mut()
+            .spawn(CollisionGroups::new(Group::GROUP_1, Group::ALL))
+            .id();
+        let wall = app
+            .world_mut()
+            .spawn(CollisionGroups::new(Group::GROUP_2, Group::ALL))
+            .id();
+        let contact = |collider1, collider2, total_force_magnitude| ContactForceEvent {
+            collider1,
+            collider2,
+            total_force: Vec2::ZERO,
+            total_force_magnitude,
+            max_force_direction: Vec2::ZERO,
+            max_force_magnitude: total_force_magnitude,
+        };
+
+        app.world_mut().send_event(contact(wall, player, 500.0));
+        app.world_mut().send_event(contact(wall, wall, 3000.0));
+        app.update();
+        assert_eq!(app.world().resource::<CameraShake>().trauma(), 0.0);
+
+        app.world_mut().send_event(contact(player, wall, 3000.0));
+        app.update();
+        assert!(app.world().resource::<CameraShake>().trauma() > 0.0);
+    }
+
+    #[test]
+    fn finds_entities_in_radius() {
+        let mut app = app(PhysicsSchedule::PostUpdate, PhysicsSettings::default());
+        let mut spawn = |x: f32| {
+            app.world_mut()
+                .spawn((
+                    Collider::ball(1.0),
+                    TransformBundle::from_transform(Transform::from_xyz(x, 0.0, 0.0)),
+                ))
+                .id()
+        };
+        let inside = spawn(5.0);
+        // The collider edge touches the radius.
+        let on_radius = spawn(11.0);
+        let outside = spawn(20.0);
+        app.update();
+        app.update();
+
+        let rapier_context = app.world().resource::<RapierContext>();
+        let entities = entities_in_radius(rapier_context, Vec2::ZERO, 10.0, QueryFilter::new());
+        assert!(entities.contains(&inside));
+        assert!(entities.contains(&on_radius));
+        assert!(!entities.contains(&outside));
+        assert_eq!(entities.len(), 2);
+    }
 }
@@ -0,0 +1,289 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier2d::prelude::*;
+
+/// Add this together with a `Collider` to an entity to get `AreaEntered` and `AreaExited`
+/// events when other colliders enter or leave it, for example to start a cutscene
+/// when the player walks into a room.
+///
+/// The collider is turned into a sensor, so it doesn't push anything away.
+/// The events contain the collider entities, so if the collider of your player
+/// is on a child entity, that child is the one in the event.
+#[derive(Component)]
+pub struct AreaTrigger {
+    /// Only colliders that are a member of these groups are tracked,
+    /// all of them by default. Colliders without `CollisionGroups` are members of all groups.
+    pub groups: Group,
+}
+
+impl Default for AreaTrigger {
+    fn default() -> Self {
+        Self { groups: Group::ALL }
+    }
+}
+
+impl AreaTrigger {
+    /// Only track colliders that are a member of the given groups.
+    pub fn new(groups: Group) -> Self {
+        Self { groups }
+    }
+}
+
+/// Sent when a tracked collider enters an `AreaTrigger`.
+#[derive(Event)]
+pub struct AreaEntered {
+    /// The entity with the `AreaTrigger`.
+    pub area: Entity,
+    /// The collider that entered the area.
+    pub entity: Entity,
+}
+
+/// Sent when a tracked collider leaves an `AreaTrigger`,
+/// including when either of them is despawned.
+#[derive(Event)]
+pub struct AreaExited {
+    /// The entity with the `AreaTrigger`.
+    pub area: Entity,
+    /// The collider that left the area.
+    pub entity: Entity,
+}
+
+fn insert_area_trigger_sensors(
+    mut commands: Commands,
+    q_triggers: Query<Entity, Added<AreaTrigger>>,
+) {
+    for entity in &q_triggers {
+        // Sensors without a rigid body are fixed, which rapier doesn't check
+        // against kinematic bodies by default, but players usually are kinematic.
+        commands.entity(entity).insert((
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+        ));
+    }
+}
+
+/// Pairs of area and collider are remembered while the collider is inside,
+/// so that every `AreaExited` has a matching `AreaEntered`,
+/// even if the area or the collider is already despawned when rapier reports it.
+fn send_area_trigger_events(
+    q_triggers: Query<&AreaTrigger>,
+    q_groups: Query<&CollisionGroups>,
+    mut ev_collision: EventReader<CollisionEvent>,
+    mut ev_area_entered: EventWriter<AreaEntered>,
+    mut ev_area_exited: EventWriter<AreaExited>,
+    mut inside: Local<HashSet<(Entity, Entity)>>,
+) {
+    for ev in ev_collision.read() {
+        match ev {
+            CollisionEvent::Started(collider1, collider2, _) => {
+                for (area, entity) in [(*collider1, *collider2), (*collider2, *collider1)] {
+                    let Ok(trigger) = q_triggers.get(area) else {
+                        continue;
+                    };
+                    let tracked = q_groups
+                        .get(entity)
+                        .map_or(true, |groups| groups.memberships.intersects(trigger.groups));
+                    if tracked && inside.insert((area, entity)) {
+                        ev_area_entered.send(AreaEntered { area, entity });
+                    }
+                }
+            }
+            CollisionEvent::Stopped(collider1, collider2, _) => {
+                for (area, entity) in [(*collider1, *collider2), (*collider2, *collider1)] {
+                    if inside.remove(&(area, entity)) {
+                        ev_area_exited.send(AreaExited { area, entity });
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct AreaTriggerPlugin;
+
+impl Plugin for AreaTriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AreaEntered>()
+            .add_event::<AreaExited>()
+            .add_systems(
+                Update,
+                (
+                    insert_area_trigger_sensors,
+                    send_area_trigger_events.run_if(resource_exists::<Events<CollisionEvent>>),
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{hierarchy::HierarchyPlugin, transform::TransformPlugin};
+
+    use super::*;
+
+    /// All area events so far, `true` for entered and `false` for exited.
+    #[derive(Resource, Default)]
+    struct AreaEvents(Vec<(Entity, Entity, bool)>);
+
+    fn collect_area_events(
+        mut area_events: ResMut<AreaEvents>,
+        mut ev_area_entered: EventReader<AreaEntered>,
+        mut ev_area_exited: EventReader<AreaExited>,
+    ) {
+        for ev in ev_area_entered.read() {
+            area_events.0.push((ev.area, ev.entity, true));
+        }
+        for ev in ev_area_exited.read() {
+            area_events.0.push((ev.area, ev.entity, false));
+        }
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            RapierPhysicsPlugin::<NoUserData>::default(),
+            AreaTriggerPlugin,
+        ))
+        .init_resource::<AreaEvents>()
+        .add_systems(Last, collect_area_events);
+        app
+    }
+
+    fn move_to(app: &mut App, entity: Entity, x: f32) {
+        app.world_mut()
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation
+            .x = x;
+        for _ in 0..3 {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn sends_enter_and_exit_events() {
+        let mut app = app();
+        let area = app
+            .world_mut()
+            .spawn((
+                TransformBundle::default(),
+                Collider::cuboid(10.0, 10.0),
+                AreaTrigger::default(),
+            ))
+            .id();
+        let player = app
+            .world_mut()
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(50.0, 0.0, 0.0)),
+                RigidBody::KinematicPositionBased,
+                Collider::ball(1.0),
+            ))
+            .id();
+        app.update();
+        assert!(app.world().resource::<AreaEvents>().0.is_empty());
+
+        move_to(&mut app, player, 0.0);
+        assert_eq!(
+            app.world().resource::<AreaEvents>().0,
+            [(area, player, true)]
+        );
+
+        move_to(&mut app, player, 50.0);
+        assert_eq!(
+            app.world().resource::<AreaEvents>().0,
+            [(area, player, true), (area, player, false)]
+        );
+    }
+
+    #[test]
+    fn ignores_untracked_groups() {
+        let mut app = app();
+        app.world_mut().spawn((
+            TransformBundle::default(),
+            Collider::cuboid(10.0, 10.0),
+            AreaTrigger::new(Group::GROUP_1),
+        ));
+        let enemy = app
+            .world_mut()
+            .spawn((
+                TransformBundle::from_transform(Transform::from_xyz(50.0, 0.0, 0.0)),
+                RigidBody::KinematicPositionBased,
+                Collider::ball(1.0),
+                CollisionGroups::new(Group::GROUP_2, Group::ALL),
+            ))
+            .id();
+        app.update();
+
+        move_to(&mut app, enemy, 0.0);
+        assert!(app.world().resource::<AreaEvents>().0.is_empty());
+    }
+
+    #[test]
+    fn sends_exit_events_on_despawn() {
+        let mut app = app();
+        let spawn_area = |app: &mut App| {
+            app.world_mut()
+                .spawn((
+                    TransformBundle::default(),
+                    Collider::cuboid(10.0, 10.0),
+                    AreaTrigger::new(Group::GROUP_1),
+                ))
+                .id()
+        };
+        let spawn_collider = |app: &mut App, groups| {
+            app.world_mut()
+                .spawn((
+                    TransformBundle::default(),
+                    RigidBody::KinematicPositionBased,
+                    Collider::ball(1.0),
+                    CollisionGroups::new(groups, Group::ALL),
+                ))
+                .id()
+        };
+        let area = spawn_area(&mut app);
+        let player = spawn_collider(&mut app, Group::GROUP_1);
+        let enemy = spawn_collider(&mut app, Group::GROUP_2);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<AreaEvents>().0,
+            [(area, player, true)]
+        );
+
+        // The untracked enemy never entered, so it doesn't exit either.
+        app.world_mut().despawn(enemy);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<AreaEvents>().0,
+            [(area, player, true)]
+        );
+
+        app.world_mut().despawn(area);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<AreaEvents>().0,
+            [(area, player, true), (area, player, false)]
+        );
+
+        let area = spawn_area(&mut app);
+        for _ in 0..3 {
+            app.update();
+        }
+        app.world_mut().despawn(player);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<AreaEvents>().0[2..],
+            [(area, player, true), (area, player, false)]
+        );
+    }
+}
@@ -1,6 +1,19 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
+use crate::{
+    audio::{AudioStats, GameAudio, SoundChannel},
+    camera::{CameraProjection, CameraShake, CameraZoom, MainCamera},
+    physics::PhysicsSettings,
+};
+
+use super::{COLLIDER_COLOR_TRANSPARENT, COLLIDER_COLOR_WHITE};
+
+/// Size of a square of the `AudioStats` overlay, relative to the height of the view.
+const AUDIO_STATS_SQUARE_SIZE: f32 = 0.015;
+/// The overlay doesn't draw more squares than this, so it stays on screen.
+const MAX_AUDIO_STATS_SQUARES: usize = 64;
+
 /// Indicates whether the game is currently in debug mode.
 /// This can be used for just debugging info to the player (developer),
 /// or it can also act as a trigger to allow cheats etc.
@@ -11,16 +24,386 @@ pub struct DebugState(pub bool);
 #[derive(Event)]
 pub struct ToggleDebugStateEvent;
 
+/// A single debug view that can be toggled independently.
+///
+/// A flag is only active while it is enabled and the `DebugState` is on,
+/// unless it is marked as `always_active`, in which case it ignores the `DebugState`.
+#[derive(Clone, Copy, Debug)]
+pub struct DebugFlag {
+    /// Whether this debug view is enabled, `true` by default.
+    pub enabled: bool,
+    /// Whether this debug view is also active when the `DebugState` is off.
+    pub always_active: bool,
+}
+
+impl Default for DebugFlag {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            always_active: false,
+        }
+    }
+}
+
+impl DebugFlag {
+    /// Whether this debug view should currently be shown.
+    pub fn is_active(&self, debug_state: &DebugState) -> bool {
+        self.enabled && (**debug_state || self.always_active)
+    }
+}
+
+/// The debug views of this crate, see `DebugFlag` for how they interact with the `DebugState`.
+#[derive(Resource, Default, Clone, Copy, Debug)]
+pub struct DebugFlags {
+    /// Rapier's collider debug rendering.
+    pub colliders: DebugFlag,
+    /// Gizmos and logs of spacial audio emitters.
+    pub spacial_audio: DebugFlag,
+    /// Circles around spacial audio emitters and the listener
+    /// that show the distance at which sounds become inaudible.
+    pub hearing_range: DebugFlag,
+    /// An overlay in the top left corner of the view that shows the `AudioStats`,
+    /// one square per playing sound and a red line at the peak.
+    pub audio_stats: DebugFlag,
+}
+
+/// Send this Event to toggle `DebugFlags::colliders`.
+#[derive(Event)]
+pub struct ToggleColliderDebugEvent;
+/// Send this Event to toggle `DebugFlags::spacial_audio`.
+#[derive(Event)]
+pub struct ToggleSpacialAudioDebugEvent;
+/// Send this Event to toggle `DebugFlags::hearing_range`.
+#[derive(Event)]
+pub struct ToggleHearingRangeDebugEvent;
+/// Send this Event to toggle `DebugFlags::audio_stats`.
+#[derive(Event)]
+pub struct ToggleAudioStatsDebugEvent;
+/// Send this Event to solo the next spacial emitter, muting all others,
+/// to identify sounds in a busy scene. Sending it on the last emitter disables the solo.
+/// Only works in debug mode, the solo is disabled when leaving it.
+#[derive(Event)]
+pub struct CycleSoloSpacialEmitterEvent;
+
+/// Send this Event to pause or resume the game with the `FrameStepper`.
+/// Only works in debug mode, the game is resumed when leaving it.
+#[derive(Event)]
+pub struct ToggleFrameStepperEvent;
+/// Send this Event to advance the game paused by the `FrameStepper` by exactly one frame.
+#[derive(Event)]
+pub struct StepFrameEvent;
+
+/// Pauses the game for frame by frame inspection, for example of the shake,
+/// physics or audio timing. Use the keys of the `FrameStepperSettings`,
+/// or send `ToggleFrameStepperEvent` and `StepFrameEvent` yourself.
+///
+/// While engaged, the virtual time (`Time<Virtual>`) and rapier's physics pipeline are paused.
+/// Systems that use the real time, like the audio playback itself, keep running.
+#[derive(Resource, Default, Debug)]
+pub struct FrameStepper {
+    engaged: bool,
+    stepping: bool,
+    was_paused: bool,
+    physics_was_active: bool,
+}
+
+/// The keys that control the `FrameStepper`, they only work in debug mode.
+#[derive(Resource, Debug)]
+pub struct FrameStepperSettings {
+    /// The key that sends `ToggleFrameStepperEvent`, `F9` by default.
+    /// `None` to only use the event.
+    pub toggle_key: Option<KeyCode>,
+    /// The key that sends `StepFrameEvent`, `F10` by default.
+    /// `None` to only use the event.
+    pub step_key: Option<KeyCode>,
+}
+
+impl Default for FrameStepperSettings {
+    fn default() -> Self {
+        Self {
+            toggle_key: Some(KeyCode::F9),
+            step_key: Some(KeyCode::F10),
+        }
+    }
+}
+
+impl FrameStepper {
+    /// Whether the game is currently paused by the frame stepper.
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+}
+
+/// What the trauma of the `CameraShake` is made of,
+/// see `CameraShake::trauma_breakdown`. Useful for debug overlays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraumaBreakdown {
+    /// The current trauma, including the sustained trauma.
+    pub trauma: f32,
+    /// The level the trauma is sustained at, see `CameraShake::set_sustained_trauma`.
+    pub sustained_trauma: f32,
+    /// Trauma that was added but is still rising, see `CameraShake::set_trauma_rise_rate`.
+    pub pending_trauma: f32,
+    /// The product of all active `CameraShake::push_shake_scale`s.
+    pub shake_scale: f32,
+    /// Whether the shake is frozen, see `CameraShake::freeze_shake`.
+    pub frozen: bool,
+}
+
+/// Send this Event to log a snapshot of all settings of this crate,
+/// useful for bug reports and tuning.
+#[derive(Event)]
+pub struct DumpRancicSettingsEvent;
+
+/// Add this to entities to only show their colliders in debug mode.
+///
+/// As long as at least one entity has this component, the colliders of all
+/// marked entities are colored with `COLLIDER_COLOR_WHITE` and all other
+/// colliders with `COLLIDER_COLOR_TRANSPARENT`.
+/// Without any marked entities all colliders are shown as usual.
+#[derive(Component)]
+pub struct DebugColliderFilter;
+
+/// The color a collider had before it got changed by the `DebugColliderFilter`.
+#[derive(Component)]
+struct FilteredColliderColor(Option<ColliderDebugColor>);
+
 fn toggle_debug_mod(mut debug_active: ResMut<DebugState>) {
     **debug_active = !**debug_active;
 }
 
+fn toggle_debug_flags(
+    mut debug_flags: ResMut<DebugFlags>,
+    mut ev_toggle_collider_debug: EventReader<ToggleColliderDebugEvent>,
+    mut ev_toggle_spacial_audio_debug: EventReader<ToggleSpacialAudioDebugEvent>,
+    mut ev_toggle_hearing_range_debug: EventReader<ToggleHearingRangeDebugEvent>,
+    mut ev_toggle_audio_stats_debug: EventReader<ToggleAudioStatsDebugEvent>,
+) {
+    for _ in ev_toggle_collider_debug.read() {
+        debug_flags.colliders.enabled = !debug_flags.colliders.enabled;
+    }
+    for _ in ev_toggle_spacial_audio_debug.read() {
+        debug_flags.spacial_audio.enabled = !debug_flags.spacial_audio.enabled;
+    }
+    for _ in ev_toggle_hearing_range_debug.read() {
+        debug_flags.hearing_range.enabled = !debug_flags.hearing_range.enabled;
+    }
+    for _ in ev_toggle_audio_stats_debug.read() {
+        debug_flags.audio_stats.enabled = !debug_flags.audio_stats.enabled;
+    }
+}
+
+fn debug_audio_stats(
+    mut gizmos: Gizmos,
+    debug_state: Res<DebugState>,
+    debug_flags: Res<DebugFlags>,
+    audio_stats: Res<AudioStats>,
+    q_camera: Query<(&GlobalTransform, &OrthographicProjection), With<MainCamera>>,
+) {
+    if !debug_flags.audio_stats.is_active(&debug_state) {
+        return;
+    }
+    let Ok((camera_transform, projection)) = q_camera.get_single() else {
+        return;
+    };
+
+    let size = projection.area.height() * AUDIO_STATS_SQUARE_SIZE;
+    let spacing = size * 1.5;
+    let origin = camera_transform.translation().truncate()
+        + Vec2::new(projection.area.min.x, projection.area.max.y)
+        + Vec2::new(size, -size);
+    for i in 0..audio_stats.playing.min(MAX_AUDIO_STATS_SQUARES) {
+        gizmos.rect_2d(
+            origin + Vec2::X * i as f32 * spacing,
+            0.0,
+            Vec2::splat(size),
+            Color::srgb(0.0, 1.0, 0.0),
+        );
+    }
+    let peak_x =
+        audio_stats.peak_playing.min(MAX_AUDIO_STATS_SQUARES) as f32 * spacing - spacing * 0.5;
+    gizmos.line_2d(
+        origin + Vec2::new(peak_x, size),
+        origin + Vec2::new(peak_x, -size),
+        Color::srgb(1.0, 0.0, 0.0),
+    );
+}
+
 fn toggle_rapier_debug(
     mut debug_context: ResMut<DebugRenderContext>,
     debug_active: Res<DebugState>,
+    debug_flags: Res<DebugFlags>,
+) {
+    let enabled = debug_flags.colliders.is_active(&debug_active);
+    if debug_context.enabled != enabled {
+        debug_context.enabled = enabled;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn filter_debug_colliders(
+    mut commands: Commands,
+    debug_active: Res<DebugState>,
+    debug_flags: Res<DebugFlags>,
+    q_filter: Query<(), With<DebugColliderFilter>>,
+    q_colliders: Query<
+        (
+            Entity,
+            Option<&ColliderDebugColor>,
+            Has<DebugColliderFilter>,
+        ),
+        (With<Collider>, Without<FilteredColliderColor>),
+    >,
+    mut q_filtered_colliders: Query<(
+        Entity,
+        &FilteredColliderColor,
+        &mut ColliderDebugColor,
+        Has<DebugColliderFilter>,
+    )>,
+) {
+    let filter_color = |selected: bool| {
+        if selected {
+            COLLIDER_COLOR_WHITE
+        } else {
+            COLLIDER_COLOR_TRANSPARENT
+        }
+    };
+
+    if debug_flags.colliders.is_active(&debug_active) && !q_filter.is_empty() {
+        for (entity, color, selected) in &q_colliders {
+            commands.entity(entity).insert((
+                FilteredColliderColor(color.copied()),
+                filter_color(selected),
+            ));
+        }
+        for (_, _, mut color, selected) in &mut q_filtered_colliders {
+            *color = filter_color(selected);
+        }
+    } else {
+        for (entity, original_color, _, _) in &q_filtered_colliders {
+            let mut entity_commands = commands.entity(entity);
+            entity_commands.remove::<FilteredColliderColor>();
+            match original_color.0 {
+                Some(color) => entity_commands.insert(color),
+                None => entity_commands.remove::<ColliderDebugColor>(),
+            };
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn dump_settings(
+    debug_active: Res<DebugState>,
+    debug_flags: Res<DebugFlags>,
+    game_audio: Res<GameAudio>,
+    audio_stats: Res<AudioStats>,
+    shake: Res<CameraShake>,
+    zoom: Res<CameraZoom>,
+    camera_projection: Res<CameraProjection>,
+    physics_settings: Res<PhysicsSettings>,
+    q_projection: Query<&OrthographicProjection, With<MainCamera>>,
+) {
+    let channel_volumes =
+        SoundChannel::ALL.map(|channel| (channel, game_audio.effective_channel_volume(channel)));
+    let projection_scale = q_projection
+        .get_single()
+        .ok()
+        .map(|projection| projection.scale);
+    info!(
+        "rancic settings:\n\
+        debug state: {}, {:?}\n\
+        audio: {:#?}\n\
+        effective channel volumes: {:?}\n\
+        audio stats: {:?}\n\
+        camera shake: {:#?}\n\
+        camera zoom: {:#?}\n\
+        camera projection: {:?}, scale: {:?}\n\
+        physics: {:#?}",
+        **debug_active,
+        *debug_flags,
+        *game_audio,
+        channel_volumes,
+        *audio_stats,
+        *shake,
+        *zoom,
+        *camera_projection,
+        projection_scale,
+        *physics_settings,
+    );
+}
+
+fn set_game_paused(
+    paused: bool,
+    time: &mut Time<Virtual>,
+    rapier_config: Option<&mut RapierConfiguration>,
+) {
+    if paused {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+    if let Some(rapier_config) = rapier_config {
+        rapier_config.physics_pipeline_active = !paused;
+    }
+}
+
+fn send_frame_stepper_key_events(
+    debug_state: Res<DebugState>,
+    settings: Res<FrameStepperSettings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut ev_toggle_frame_stepper: EventWriter<ToggleFrameStepperEvent>,
+    mut ev_step_frame: EventWriter<StepFrameEvent>,
+) {
+    if !**debug_state {
+        return;
+    }
+    if settings
+        .toggle_key
+        .is_some_and(|key| keys.just_pressed(key))
+    {
+        ev_toggle_frame_stepper.send(ToggleFrameStepperEvent);
+    }
+    if settings.step_key.is_some_and(|key| keys.just_pressed(key)) {
+        ev_step_frame.send(StepFrameEvent);
+    }
+}
+
+/// Runs in `Last`, so that a step unpauses the game for exactly the next frame.
+fn update_frame_stepper(
+    debug_state: Res<DebugState>,
+    mut frame_stepper: ResMut<FrameStepper>,
+    mut time: ResMut<Time<Virtual>>,
+    mut rapier_config: Option<ResMut<RapierConfiguration>>,
+    mut ev_toggle_frame_stepper: EventReader<ToggleFrameStepperEvent>,
+    mut ev_step_frame: EventReader<StepFrameEvent>,
 ) {
-    if debug_context.enabled != **debug_active {
-        debug_context.enabled = **debug_active;
+    if frame_stepper.stepping {
+        frame_stepper.stepping = false;
+        set_game_paused(true, &mut time, rapier_config.as_deref_mut());
+    }
+
+    let toggled = ev_toggle_frame_stepper.read().count() % 2 == 1;
+    let engage = **debug_state && (frame_stepper.engaged != toggled);
+    if engage && !frame_stepper.engaged {
+        frame_stepper.was_paused = time.is_paused();
+        frame_stepper.physics_was_active = rapier_config
+            .as_ref()
+            .is_none_or(|rapier_config| rapier_config.physics_pipeline_active);
+        set_game_paused(true, &mut time, rapier_config.as_deref_mut());
+    } else if !engage && frame_stepper.engaged {
+        if !frame_stepper.was_paused {
+            time.unpause();
+        }
+        if let Some(rapier_config) = rapier_config.as_deref_mut() {
+            rapier_config.physics_pipeline_active = frame_stepper.physics_was_active;
+        }
+    }
+    frame_stepper.engaged = engage;
+
+    let step = ev_step_frame.read().count() > 0;
+    if frame_stepper.engaged && step {
+        frame_stepper.stepping = true;
+        set_game_paused(false, &mut time, rapier_config.as_deref_mut());
     }
 }
 
@@ -29,13 +412,93 @@ pub struct DebugPlugin;
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<DebugState>()
+            .init_resource::<DebugFlags>()
+            .init_resource::<FrameStepper>()
+            .init_resource::<FrameStepperSettings>()
             .add_event::<ToggleDebugStateEvent>()
+            .add_event::<ToggleColliderDebugEvent>()
+            .add_event::<ToggleSpacialAudioDebugEvent>()
+            .add_event::<ToggleHearingRangeDebugEvent>()
+            .add_event::<ToggleAudioStatsDebugEvent>()
+            .add_event::<CycleSoloSpacialEmitterEvent>()
+            .add_event::<DumpRancicSettingsEvent>()
+            .add_event::<ToggleFrameStepperEvent>()
+            .add_event::<StepFrameEvent>()
             .add_systems(
                 Update,
                 (
                     toggle_debug_mod.run_if(on_event::<ToggleDebugStateEvent>()),
-                    toggle_rapier_debug,
+                    (toggle_debug_flags, toggle_rapier_debug).chain(),
+                    filter_debug_colliders,
+                    dump_settings.run_if(on_event::<DumpRancicSettingsEvent>()),
+                    debug_audio_stats,
+                    send_frame_stepper_key_events.run_if(resource_exists::<ButtonInput<KeyCode>>),
                 ),
-            );
+            )
+            .add_systems(Last, update_frame_stepper);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{gizmos::gizmos::GizmoStorage, time::TimeUpdateStrategy};
+
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(100);
+
+    fn app() -> App {
+        let mut gizmo_config = GizmoConfigStore::default();
+        gizmo_config.insert(GizmoConfig::default(), DefaultGizmoConfigGroup);
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, DebugPlugin))
+            .insert_resource(gizmo_config)
+            .init_resource::<GizmoStorage<DefaultGizmoConfigGroup, ()>>()
+            .init_resource::<DebugRenderContext>()
+            .init_resource::<AudioStats>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(DebugState(true))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.press(key);
+        app.update();
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.release(key);
+        keys.clear();
+    }
+
+    fn elapsed(app: &App) -> Duration {
+        app.world().resource::<Time<Virtual>>().elapsed()
+    }
+
+    #[test]
+    fn step_advances_exactly_one_frame() {
+        let mut app = app();
+        app.update();
+        press(&mut app, KeyCode::F9);
+        assert!(app.world().resource::<FrameStepper>().is_engaged());
+
+        let paused_at = elapsed(&app);
+        app.update();
+        app.update();
+        assert_eq!(elapsed(&app), paused_at);
+
+        press(&mut app, KeyCode::F10);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(elapsed(&app), paused_at + FRAME);
+
+        press(&mut app, KeyCode::F9);
+        app.update();
+        assert!(!app.world().resource::<FrameStepper>().is_engaged());
+        assert_eq!(elapsed(&app), paused_at + FRAME * 2);
     }
 }
@@ -0,0 +1,134 @@
+use bevy_rapier2d::prelude::*;
+
+/// `Group` of the player.
+pub const GROUP_PLAYER: Group = Group::GROUP_1;
+/// `Group` of enemies.
+pub const GROUP_ENEMY: Group = Group::GROUP_2;
+/// `Group` of walls and other static level geometry.
+pub const GROUP_WALL: Group = Group::GROUP_3;
+/// `Group` of projectiles.
+pub const GROUP_PROJECTILE: Group = Group::GROUP_4;
+/// `Group` of pickups.
+pub const GROUP_PICKUP: Group = Group::GROUP_5;
+
+/// `CollisionGroups` of the player.
+/// Collides with walls, enemies and projectiles.
+pub const COLLISION_GROUPS_PLAYER: CollisionGroups = GameLayer::Player.collision_groups();
+/// `CollisionGroups` of enemies.
+/// Collide with walls, the player and projectiles.
+pub const COLLISION_GROUPS_ENEMY: CollisionGroups = GameLayer::Enemy.collision_groups();
+/// `CollisionGroups` of walls.
+/// Collide with everything.
+pub const COLLISION_GROUPS_WALL: CollisionGroups = GameLayer::Wall.collision_groups();
+/// `CollisionGroups` of projectiles.
+/// Collide with walls, the player and enemies.
+pub const COLLISION_GROUPS_PROJECTILE: CollisionGroups = GameLayer::Projectile.collision_groups();
+/// `CollisionGroups` of pickups.
+/// Only collide with walls, use a sensor to detect the player picking them up.
+pub const COLLISION_GROUPS_PICKUP: CollisionGroups = GameLayer::Pickup.collision_groups();
+
+/// Opinionated collision layers that most top down games need.
+/// You don't have to use them, but they save you from setting up the same groups every time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameLayer {
+    /// The player.
+    Player,
+    /// Enemies.
+    Enemy,
+    /// Walls and other static level geometry.
+    Wall,
+    /// Projectiles.
+    Projectile,
+    /// Pickups.
+    Pickup,
+}
+
+impl GameLayer {
+    /// The `Group` this layer is a member of.
+    pub const fn group(self) -> Group {
+        match self {
+            GameLayer::Player => GROUP_PLAYER,
+            GameLayer::Enemy => GROUP_ENEMY,
+            GameLayer::Wall => GROUP_WALL,
+            GameLayer::Projectile => GROUP_PROJECTILE,
+            GameLayer::Pickup => GROUP_PICKUP,
+        }
+    }
+
+    /// The `Group`s this layer collides with.
+    pub const fn filter(self) -> Group {
+        match self {
+            GameLayer::Player => GROUP_WALL.union(GROUP_ENEMY).union(GROUP_PROJECTILE),
+            GameLayer::Enemy => GROUP_WALL.union(GROUP_PLAYER).union(GROUP_PROJECTILE),
+            GameLayer::Wall => GROUP_PLAYER
+                .union(GROUP_ENEMY)
+                .union(GROUP_PROJECTILE)
+                .union(GROUP_PICKUP),
+            GameLayer::Projectile => GROUP_WALL.union(GROUP_PLAYER).union(GROUP_ENEMY),
+            GameLayer::Pickup => GROUP_WALL,
+        }
+    }
+
+    /// The `CollisionGroups` of this layer.
+    pub const fn collision_groups(self) -> CollisionGroups {
+        CollisionGroups::new(self.group(), self.filter())
+    }
+}
+
+impl From<GameLayer> for Group {
+    fn from(layer: GameLayer) -> Self {
+        layer.group()
+    }
+}
+
+impl From<GameLayer> for CollisionGroups {
+    fn from(layer: GameLayer) -> Self {
+        layer.collision_groups()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYERS: [GameLayer; 5] = [
+        GameLayer::Player,
+        GameLayer::Enemy,
+        GameLayer::Wall,
+        GameLayer::Projectile,
+        GameLayer::Pickup,
+    ];
+
+    /// Whether `a` wants to collide with `b`, rapier only lets them collide
+    /// if this is true in both directions.
+    fn filters(a: GameLayer, b: GameLayer) -> bool {
+        a.collision_groups()
+            .filters
+            .intersects(b.collision_groups().memberships)
+    }
+
+    #[test]
+    fn interaction_matrix() {
+        for (a, b, collides) in [
+            (GameLayer::Player, GameLayer::Wall, true),
+            (GameLayer::Player, GameLayer::Enemy, true),
+            (GameLayer::Player, GameLayer::Pickup, false),
+            (GameLayer::Pickup, GameLayer::Wall, true),
+            (GameLayer::Pickup, GameLayer::Enemy, false),
+            (GameLayer::Projectile, GameLayer::Projectile, false),
+            (GameLayer::Projectile, GameLayer::Enemy, true),
+        ] {
+            assert_eq!(filters(a, b), collides, "{:?} x {:?}", a, b);
+            assert_eq!(filters(b, a), collides, "{:?} x {:?}", b, a);
+        }
+    }
+
+    #[test]
+    fn filters_are_symmetric() {
+        for a in LAYERS {
+            for b in LAYERS {
+                assert_eq!(filters(a, b), filters(b, a), "{:?} x {:?}", a, b);
+            }
+        }
+    }
+}
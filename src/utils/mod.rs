@@ -1,6 +1,7 @@
 pub mod debug;
+pub mod layers;
 
-use bevy::prelude::*;
+use bevy::{math::bounding::Aabb2d, prelude::*};
 use bevy_rapier2d::prelude::*;
 
 /// Empty `CollisionGroups` for Rapier.
@@ -45,7 +46,24 @@ pub fn quat_from_vec2(direction: Vec2) -> Quat {
     if direction == Vec2::ZERO {
         return Quat::IDENTITY;
     }
-    Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, Vec2::X.angle_between(direction))
+    Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, angle_from_vec2(direction))
+}
+
+/// The angle between `Vec2::X` and the given direction in radians,
+/// using the same convention as `quat_from_vec2`.
+/// Returns `0.0` for `Vec2::ZERO`.
+pub fn angle_from_vec2(direction: Vec2) -> f32 {
+    if direction == Vec2::ZERO {
+        return 0.0;
+    }
+    Vec2::X.angle_between(direction)
+}
+
+/// Rotate the given `Vec2` counterclockwise by the angle in radians.
+/// This is consistent with `angle_from_vec2`, so
+/// `rotate_vec2(Vec2::X, angle_from_vec2(dir))` equals `dir.normalize()`.
+pub fn rotate_vec2(v: Vec2, angle: f32) -> Vec2 {
+    Vec2::from_angle(angle).rotate(v)
 }
 
 /// Convert `Vec3` to `Quat` by truncating the z value,
@@ -53,3 +71,95 @@ pub fn quat_from_vec2(direction: Vec2) -> Quat {
 pub fn quat_from_vec3(direction: Vec3) -> Quat {
     quat_from_vec2(direction.truncate())
 }
+
+/// Create an `Aabb2d` from its center and full size.
+/// Negative sizes are treated as positive.
+pub fn aabb_from_center_size(center: Vec2, size: Vec2) -> Aabb2d {
+    Aabb2d::new(center, size.abs() / 2.0)
+}
+
+/// Create an `Aabb2d` from the translation of the given `Transform`
+/// and the full (unscaled) size, which will be multiplied by the transform's scale.
+/// The rotation of the transform is ignored.
+pub fn aabb_from_transform(transform: &Transform, size: Vec2) -> Aabb2d {
+    aabb_from_center_size(
+        transform.translation.truncate(),
+        size * transform.scale.truncate(),
+    )
+}
+
+/// Create the smallest `Aabb2d` that encloses all the given points.
+/// Returns `None` if there are no points.
+pub fn aabb_enclosing(points: impl IntoIterator<Item = Vec2>) -> Option<Aabb2d> {
+    let mut points = points.into_iter();
+    let first = points.next()?;
+    let (min, max) = points.fold((first, first), |(min, max), p| (min.min(p), max.max(p)));
+    Some(Aabb2d { min, max })
+}
+
+/// Ease in and out (smoothstep), `t` is clamped between `0.0..1.0`.
+/// Use as the easing function of transitions, for a linear easing use `|t| t`.
+pub fn ease_in_out(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aabb_from_negative_size() {
+        let aabb = aabb_from_center_size(Vec2::new(10.0, 20.0), Vec2::new(-4.0, 6.0));
+        assert_eq!(aabb.min, Vec2::new(8.0, 17.0));
+        assert_eq!(aabb.max, Vec2::new(12.0, 23.0));
+    }
+
+    #[test]
+    fn aabb_from_scaled_transform() {
+        let transform = Transform::from_xyz(10.0, -10.0, 5.0)
+            .with_scale(Vec3::new(2.0, -3.0, 1.0))
+            .with_rotation(Quat::from_rotation_z(1.0));
+        let aabb = aabb_from_transform(&transform, Vec2::new(4.0, 2.0));
+        assert_eq!(aabb.min, Vec2::new(6.0, -13.0));
+        assert_eq!(aabb.max, Vec2::new(14.0, -7.0));
+    }
+
+    #[test]
+    fn aabb_enclosing_points() {
+        assert!(aabb_enclosing([]).is_none());
+
+        let aabb = aabb_enclosing([
+            Vec2::new(1.0, -2.0),
+            Vec2::new(-3.0, 4.0),
+            Vec2::new(2.0, 0.0),
+        ])
+        .unwrap();
+        assert_eq!(aabb.min, Vec2::new(-3.0, -2.0));
+        assert_eq!(aabb.max, Vec2::new(2.0, 4.0));
+    }
+
+    #[test]
+    fn rotate_vec2_is_consistent_with_angle_from_vec2() {
+        for direction in [
+            Vec2::new(3.0, 1.0),
+            Vec2::new(-2.0, 5.0),
+            Vec2::new(-4.0, -1.0),
+            Vec2::new(1.0, -6.0),
+            Vec2::NEG_X,
+            Vec2::NEG_Y,
+        ] {
+            let angle = angle_from_vec2(direction);
+            let rotated = rotate_vec2(Vec2::X, angle);
+            assert!(
+                rotated.abs_diff_eq(direction.normalize(), 1e-5),
+                "{} rotated by {} is {}",
+                direction,
+                angle,
+                rotated
+            );
+        }
+        // Directions below the x-axis have negative angles.
+        assert!(angle_from_vec2(Vec2::new(1.0, -6.0)) < 0.0);
+    }
+}